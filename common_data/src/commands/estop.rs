@@ -0,0 +1,69 @@
+#[derive(Debug, Clone)]
+pub struct EStop {
+    pub engaged: bool,
+    pub checksum: i16,
+    pub packet: Option<[u8; 4]>,
+}
+
+pub const COMMAND_NUMBER: u8 = 2;
+
+#[derive(Debug, Clone)]
+pub enum EStopPacketDecodeError {
+    ChecksumNotValid,
+    NotEStopPacket,
+}
+
+impl EStop {
+    pub fn new(engaged: bool) -> Self {
+        let mut estop = EStop {
+            engaged,
+            checksum: 0,
+            packet: None,
+        };
+
+        estop.set_checksum();
+
+        return estop;
+    }
+
+    pub fn set_checksum(&mut self) {
+        self.checksum = i16::from(self.engaged as u8) + i16::from(COMMAND_NUMBER);
+    }
+
+    pub fn generate_packet(&mut self) -> [u8; 4] {
+        let checksum_bytes: [u8; 2] = self.checksum.to_be_bytes();
+
+        let created_packet: [u8; 4] = [
+            COMMAND_NUMBER,
+            self.engaged as u8,
+            checksum_bytes[0],
+            checksum_bytes[1],
+        ];
+
+        self.packet = Some(created_packet);
+
+        return created_packet;
+    }
+
+    pub fn decode_packet(packet: [u8; 4]) -> Result<Self, EStopPacketDecodeError> {
+        if packet[0] != COMMAND_NUMBER {
+            return Err(EStopPacketDecodeError::NotEStopPacket);
+        }
+
+        let checksum = ((packet[2] as i16) << 8) | packet[3] as i16;
+
+        let mut working_estop = EStop {
+            engaged: packet[1] != 0,
+            checksum,
+            packet: Some(packet),
+        };
+
+        working_estop.set_checksum();
+
+        if working_estop.checksum != checksum {
+            return Err(EStopPacketDecodeError::ChecksumNotValid);
+        }
+
+        return Ok(working_estop);
+    }
+}
@@ -1 +1,5 @@
+pub mod estop;
+pub mod motion;
 pub mod movement;
+pub mod ping;
+pub mod status;
@@ -0,0 +1,127 @@
+// Streamed by the car to the driver so a rig can move the seat with the car.
+// Floats are big endian, the sequence lets late packets be thrown away.
+#[derive(Debug, Clone)]
+pub struct Motion {
+    pub sequence: u16,
+    // m/s, negative in reverse
+    pub speed: f32,
+    // m/s^2 forward, right, up. Up includes gravity like an accelerometer.
+    pub acceleration: [f32; 3],
+    // Radians per second, positive turning right
+    pub yaw_rate: f32,
+    // Radians of the front wheels, positive right
+    pub steering_angle: f32,
+    // Biggest change in velocity from hitting something since the last
+    // packet, m/s forward and right. Zero when nothing was hit.
+    pub impact: [f32; 2],
+    pub checksum: i16,
+    pub packet: Option<[u8; PACKET_SIZE]>,
+}
+
+pub const COMMAND_NUMBER: u8 = 4;
+
+pub const PACKET_SIZE: usize = 37;
+
+#[derive(Debug, Clone)]
+pub enum MotionPacketDecodeError {
+    ChecksumNotValid,
+    NotMotionPacket,
+}
+
+impl Motion {
+    pub fn new(
+        sequence: u16,
+        speed: f32,
+        acceleration: [f32; 3],
+        yaw_rate: f32,
+        steering_angle: f32,
+        impact: [f32; 2],
+    ) -> Self {
+        let mut motion = Motion {
+            sequence,
+            speed,
+            acceleration,
+            yaw_rate,
+            steering_angle,
+            impact,
+            checksum: 0,
+            packet: None,
+        };
+
+        motion.set_checksum();
+
+        return motion;
+    }
+
+    // Everything after the command number and before the checksum
+    fn payload(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(PACKET_SIZE - 3);
+
+        payload.extend_from_slice(&self.sequence.to_be_bytes());
+        payload.extend_from_slice(&self.speed.to_be_bytes());
+        for axis in self.acceleration.iter() {
+            payload.extend_from_slice(&axis.to_be_bytes());
+        }
+        payload.extend_from_slice(&self.yaw_rate.to_be_bytes());
+        payload.extend_from_slice(&self.steering_angle.to_be_bytes());
+        for axis in self.impact.iter() {
+            payload.extend_from_slice(&axis.to_be_bytes());
+        }
+
+        return payload;
+    }
+
+    pub fn set_checksum(&mut self) {
+        let mut checksum = i16::from(COMMAND_NUMBER);
+
+        for byte in self.payload().iter() {
+            checksum += i16::from(*byte);
+        }
+
+        self.checksum = checksum;
+    }
+
+    pub fn generate_packet(&mut self) -> [u8; PACKET_SIZE] {
+        self.set_checksum();
+
+        let mut created_packet = [0u8; PACKET_SIZE];
+
+        created_packet[0] = COMMAND_NUMBER;
+        created_packet[1..PACKET_SIZE - 2].copy_from_slice(&self.payload());
+        created_packet[PACKET_SIZE - 2..].copy_from_slice(&self.checksum.to_be_bytes());
+
+        self.packet = Some(created_packet);
+
+        return created_packet;
+    }
+
+    pub fn decode_packet(packet: [u8; PACKET_SIZE]) -> Result<Self, MotionPacketDecodeError> {
+        if packet[0] != COMMAND_NUMBER {
+            return Err(MotionPacketDecodeError::NotMotionPacket);
+        }
+
+        let checksum = i16::from_be_bytes([packet[PACKET_SIZE - 2], packet[PACKET_SIZE - 1]]);
+        let float = |at: usize| -> f32 {
+            f32::from_be_bytes([packet[at], packet[at + 1], packet[at + 2], packet[at + 3]])
+        };
+
+        let mut working_motion = Motion {
+            sequence: u16::from_be_bytes([packet[1], packet[2]]),
+            speed: float(3),
+            acceleration: [float(7), float(11), float(15)],
+            yaw_rate: float(19),
+            steering_angle: float(23),
+            impact: [float(27), float(31)],
+            checksum,
+            packet: Some(packet),
+        };
+
+        working_motion.set_checksum();
+
+        if working_motion.checksum != checksum {
+            return Err(MotionPacketDecodeError::ChecksumNotValid);
+        }
+
+        return Ok(working_motion);
+    }
+}
@@ -6,7 +6,7 @@ pub struct Movement {
     pub packet: Option<[u8; 5]>,
 }
 
-const COMMAND_NUMBER: u8 = 1;
+pub const COMMAND_NUMBER: u8 = 1;
 
 #[derive(Debug, Clone)]
 pub enum MovementSetError {
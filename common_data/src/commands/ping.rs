@@ -0,0 +1,79 @@
+// Sent by the car to the driver, who sends the same packet straight back so
+// the car can measure round trip time and packet loss
+#[derive(Debug, Clone)]
+pub struct Ping {
+    pub sequence: u16,
+    pub checksum: i16,
+    pub packet: Option<[u8; 5]>,
+}
+
+pub const COMMAND_NUMBER: u8 = 3;
+
+#[derive(Debug, Clone)]
+pub enum PingPacketDecodeError {
+    ChecksumNotValid,
+    NotPingPacket,
+}
+
+impl Ping {
+    pub fn new(sequence: u16) -> Self {
+        let mut ping = Ping {
+            sequence,
+            checksum: 0,
+            packet: None,
+        };
+
+        ping.set_checksum();
+
+        return ping;
+    }
+
+    pub fn set_checksum(&mut self) {
+        let mut checksum = i16::from(COMMAND_NUMBER);
+
+        for byte in self.sequence.to_be_bytes().iter() {
+            checksum += i16::from(*byte);
+        }
+
+        self.checksum = checksum;
+    }
+
+    pub fn generate_packet(&mut self) -> [u8; 5] {
+        let sequence_bytes: [u8; 2] = self.sequence.to_be_bytes();
+        let checksum_bytes: [u8; 2] = self.checksum.to_be_bytes();
+
+        let created_packet: [u8; 5] = [
+            COMMAND_NUMBER,
+            sequence_bytes[0],
+            sequence_bytes[1],
+            checksum_bytes[0],
+            checksum_bytes[1],
+        ];
+
+        self.packet = Some(created_packet);
+
+        return created_packet;
+    }
+
+    pub fn decode_packet(packet: [u8; 5]) -> Result<Self, PingPacketDecodeError> {
+        if packet[0] != COMMAND_NUMBER {
+            return Err(PingPacketDecodeError::NotPingPacket);
+        }
+
+        let checksum = ((packet[3] as i16) << 8) | packet[4] as i16;
+
+        let mut working_ping = Ping {
+            sequence: u16::from_be_bytes([packet[1], packet[2]]),
+            checksum,
+            packet: Some(packet),
+        };
+
+        working_ping.set_checksum();
+
+        if working_ping.checksum != checksum {
+            return Err(PingPacketDecodeError::ChecksumNotValid);
+        }
+
+        return Ok(working_ping);
+    }
+}
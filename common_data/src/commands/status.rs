@@ -0,0 +1,109 @@
+// Streamed by the car to the driver a few times a second for rig dashboards,
+// so they work without the server. Floats are big endian.
+#[derive(Debug, Clone, Default)]
+pub struct Status {
+    pub sequence: u16,
+    // m/s, negative in reverse
+    pub speed: f32,
+    // Percent
+    pub battery_charge: u8,
+    // Degrees clockwise from north
+    pub heading: f32,
+    // Round trip to the driver in ms, 0 when not yet measured
+    pub latency: u16,
+    pub laps: u16,
+    // Seconds, 0.0 before the car first crosses the line
+    pub lap_time: f32,
+    // Seconds, 0.0 until a lap has been completed
+    pub last_lap: f32,
+    pub best_lap: f32,
+    pub checksum: i16,
+    pub packet: Option<[u8; PACKET_SIZE]>,
+}
+
+pub const COMMAND_NUMBER: u8 = 5;
+
+pub const PACKET_SIZE: usize = 30;
+
+#[derive(Debug, Clone)]
+pub enum StatusPacketDecodeError {
+    ChecksumNotValid,
+    NotStatusPacket,
+}
+
+impl Status {
+    // Everything after the command number and before the checksum
+    fn payload(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(PACKET_SIZE - 3);
+
+        payload.extend_from_slice(&self.sequence.to_be_bytes());
+        payload.extend_from_slice(&self.speed.to_be_bytes());
+        payload.push(self.battery_charge);
+        payload.extend_from_slice(&self.heading.to_be_bytes());
+        payload.extend_from_slice(&self.latency.to_be_bytes());
+        payload.extend_from_slice(&self.laps.to_be_bytes());
+        payload.extend_from_slice(&self.lap_time.to_be_bytes());
+        payload.extend_from_slice(&self.last_lap.to_be_bytes());
+        payload.extend_from_slice(&self.best_lap.to_be_bytes());
+
+        return payload;
+    }
+
+    pub fn set_checksum(&mut self) {
+        let mut checksum = i16::from(COMMAND_NUMBER);
+
+        for byte in self.payload().iter() {
+            checksum += i16::from(*byte);
+        }
+
+        self.checksum = checksum;
+    }
+
+    pub fn generate_packet(&mut self) -> [u8; PACKET_SIZE] {
+        self.set_checksum();
+
+        let mut created_packet = [0u8; PACKET_SIZE];
+
+        created_packet[0] = COMMAND_NUMBER;
+        created_packet[1..PACKET_SIZE - 2].copy_from_slice(&self.payload());
+        created_packet[PACKET_SIZE - 2..].copy_from_slice(&self.checksum.to_be_bytes());
+
+        self.packet = Some(created_packet);
+
+        return created_packet;
+    }
+
+    pub fn decode_packet(packet: [u8; PACKET_SIZE]) -> Result<Self, StatusPacketDecodeError> {
+        if packet[0] != COMMAND_NUMBER {
+            return Err(StatusPacketDecodeError::NotStatusPacket);
+        }
+
+        let checksum = i16::from_be_bytes([packet[PACKET_SIZE - 2], packet[PACKET_SIZE - 1]]);
+        let float = |at: usize| -> f32 {
+            f32::from_be_bytes([packet[at], packet[at + 1], packet[at + 2], packet[at + 3]])
+        };
+        let short = |at: usize| -> u16 { u16::from_be_bytes([packet[at], packet[at + 1]]) };
+
+        let mut working_status = Status {
+            sequence: short(1),
+            speed: float(3),
+            battery_charge: packet[7],
+            heading: float(8),
+            latency: short(12),
+            laps: short(14),
+            lap_time: float(16),
+            last_lap: float(20),
+            best_lap: float(24),
+            checksum,
+            packet: Some(packet),
+        };
+
+        working_status.set_checksum();
+
+        if working_status.checksum != checksum {
+            return Err(StatusPacketDecodeError::ChecksumNotValid);
+        }
+
+        return Ok(working_status);
+    }
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+// Per car corrections between the driver's input and the servo and ESC. All
+// values are percentages in the same units as a movement command.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Calibration {
+    // Added to the steering so the car drives straight at zero, -100 to 100
+    pub steering_trim: i8,
+    // Largest throttle the car is allowed forward and in reverse, 0 to 100
+    pub throttle_forward_limit: u8,
+    pub throttle_reverse_limit: u8,
+    // Steering at full lock left and right, 0 to 100
+    pub steering_left_endpoint: u8,
+    pub steering_right_endpoint: u8,
+    // Flip the direction for reversed servo or motor wiring
+    pub invert_throttle: bool,
+    pub invert_steering: bool,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        return Calibration {
+            steering_trim: 0,
+            throttle_forward_limit: 100,
+            throttle_reverse_limit: 100,
+            steering_left_endpoint: 100,
+            steering_right_endpoint: 100,
+            invert_throttle: false,
+            invert_steering: false,
+        };
+    }
+}
+
+impl Calibration {
+    pub fn is_valid(&self) -> bool {
+        return self.steering_trim >= -100
+            && self.steering_trim <= 100
+            && self.throttle_forward_limit <= 100
+            && self.throttle_reverse_limit <= 100
+            && self.steering_left_endpoint <= 100
+            && self.steering_right_endpoint <= 100;
+    }
+
+    // throttle and steering are -1.0 to 1.0, steering positive to the right
+    pub fn apply(&self, throttle: f32, steering: f32) -> (f32, f32) {
+        let throttle = throttle.clamp(-1.0, 1.0);
+        let steering = steering.clamp(-1.0, 1.0);
+
+        let throttle = if throttle >= 0.0 {
+            throttle * f32::from(self.throttle_forward_limit) / 100.0
+        } else {
+            throttle * f32::from(self.throttle_reverse_limit) / 100.0
+        };
+
+        let steering = if steering >= 0.0 {
+            steering * f32::from(self.steering_right_endpoint) / 100.0
+        } else {
+            steering * f32::from(self.steering_left_endpoint) / 100.0
+        };
+
+        let steering = (steering + f32::from(self.steering_trim) / 100.0).clamp(-1.0, 1.0);
+
+        let throttle = if self.invert_throttle {
+            -throttle
+        } else {
+            throttle
+        };
+
+        let steering = if self.invert_steering {
+            -steering
+        } else {
+            steering
+        };
+
+        return (throttle, steering);
+    }
+}
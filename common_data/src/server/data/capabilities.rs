@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    Accelerate,
+    Steering,
+    Pitch,
+    Yaw,
+    Roll,
+    Height,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Sensor {
+    Gps,
+    Heading,
+    WheelSpeed,
+    Accelerometer,
+    // Forward facing ultrasonic or lidar
+    Range,
+    Camera,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CameraGimbal {
+    pub pan: bool,
+    pub tilt: bool,
+}
+
+// What a car can do, sent by the car when it connects so drivers can hide
+// controls the car does not have
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    // Command numbers from common_data::commands the car will act on
+    pub commands: Vec<u8>,
+    pub axes: Vec<Axis>,
+    #[serde(default)]
+    pub camera_gimbal: Option<CameraGimbal>,
+    // m/s
+    pub max_speed: f32,
+    pub sensors: Vec<Sensor>,
+}
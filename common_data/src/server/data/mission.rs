@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Waypoint {
+    // Degrees
+    pub latitude: f64,
+    pub longitude: f64,
+    // m/s to drive towards this waypoint, the car picks one when unset
+    #[serde(default)]
+    pub speed: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Mission {
+    pub uuid: String,
+    pub waypoints: Vec<Waypoint>,
+    // Times to drive the route, going from the last waypoint back to the first
+    pub laps: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum MissionState {
+    Running,
+    Complete,
+    // Stopped by the driver or an E-stop
+    Aborted,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MissionProgress {
+    pub uuid: String,
+    pub state: MissionState,
+    // Index of the waypoint being driven to
+    pub waypoint: u32,
+    pub waypoint_count: u32,
+    pub lap: u32,
+    // Meters
+    pub distance_to_waypoint: f32,
+}
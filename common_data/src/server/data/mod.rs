@@ -1,2 +1,6 @@
+pub mod calibration;
+pub mod capabilities;
 pub mod jwt_claims;
+pub mod mission;
+pub mod position;
 pub mod telementry;
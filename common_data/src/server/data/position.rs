@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+// WGS84 ellipsoid
+const SEMI_MAJOR_AXIS: f64 = 6_378_137.0;
+const FLATTENING: f64 = 1.0 / 298.257_223_563;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum FixType {
+    NoFix,
+    Fix2D,
+    Fix3D,
+    Dgps,
+    RtkFloat,
+    RtkFixed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Position {
+    // Degrees
+    pub latitude: f64,
+    pub longitude: f64,
+    // Meters above the WGS84 ellipsoid
+    pub altitude: f64,
+    // Meters, one standard deviation
+    pub horizontal_accuracy: f32,
+    pub fix: FixType,
+}
+
+impl Position {
+    pub fn new(latitude: f64, longitude: f64, altitude: f64) -> Self {
+        return Position {
+            latitude,
+            longitude,
+            altitude,
+            horizontal_accuracy: 0.0,
+            fix: FixType::Fix3D,
+        };
+    }
+
+    pub fn has_fix(&self) -> bool {
+        return self.fix != FixType::NoFix;
+    }
+
+    // Meters east, north and up of origin
+    pub fn to_enu(&self, origin: &Position) -> [f64; 3] {
+        let point = to_ecef(self.latitude, self.longitude, self.altitude);
+        let reference = to_ecef(origin.latitude, origin.longitude, origin.altitude);
+
+        let dx = point[0] - reference[0];
+        let dy = point[1] - reference[1];
+        let dz = point[2] - reference[2];
+
+        let lat = origin.latitude.to_radians();
+        let lon = origin.longitude.to_radians();
+
+        let east = -lon.sin() * dx + lon.cos() * dy;
+        let north = -lat.sin() * lon.cos() * dx - lat.sin() * lon.sin() * dy + lat.cos() * dz;
+        let up = lat.cos() * lon.cos() * dx + lat.cos() * lon.sin() * dy + lat.sin() * dz;
+
+        return [east, north, up];
+    }
+
+    // Position of a point given in meters east, north and up of origin. The
+    // accuracy and fix of the result are copied from origin.
+    pub fn from_enu(origin: &Position, enu: [f64; 3]) -> Position {
+        let lat = origin.latitude.to_radians();
+        let lon = origin.longitude.to_radians();
+        let [east, north, up] = enu;
+
+        let reference = to_ecef(origin.latitude, origin.longitude, origin.altitude);
+
+        let x = reference[0] - lon.sin() * east - lat.sin() * lon.cos() * north
+            + lat.cos() * lon.cos() * up;
+        let y = reference[1] + lon.cos() * east - lat.sin() * lon.sin() * north
+            + lat.cos() * lon.sin() * up;
+        let z = reference[2] + lat.cos() * north + lat.sin() * up;
+
+        let (latitude, longitude, altitude) = from_ecef([x, y, z]);
+
+        return Position {
+            latitude,
+            longitude,
+            altitude,
+            horizontal_accuracy: origin.horizontal_accuracy,
+            fix: origin.fix,
+        };
+    }
+
+    // Older cars only report the f32 gps pair
+    pub fn from_legacy(gps: [f32; 2]) -> Position {
+        return Position {
+            latitude: f64::from(gps[0]),
+            longitude: f64::from(gps[1]),
+            altitude: 0.0,
+            horizontal_accuracy: 0.0,
+            fix: FixType::Fix2D,
+        };
+    }
+
+    pub fn to_legacy(&self) -> [f32; 2] {
+        return [self.latitude as f32, self.longitude as f32];
+    }
+}
+
+fn eccentricity_squared() -> f64 {
+    return FLATTENING * (2.0 - FLATTENING);
+}
+
+fn to_ecef(latitude: f64, longitude: f64, altitude: f64) -> [f64; 3] {
+    let lat = latitude.to_radians();
+    let lon = longitude.to_radians();
+    let e2 = eccentricity_squared();
+
+    let prime_vertical = SEMI_MAJOR_AXIS / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+
+    return [
+        (prime_vertical + altitude) * lat.cos() * lon.cos(),
+        (prime_vertical + altitude) * lat.cos() * lon.sin(),
+        (prime_vertical * (1.0 - e2) + altitude) * lat.sin(),
+    ];
+}
+
+fn from_ecef(ecef: [f64; 3]) -> (f64, f64, f64) {
+    let [x, y, z] = ecef;
+    let e2 = eccentricity_squared();
+
+    let longitude = y.atan2(x);
+    let p = (x * x + y * y).sqrt();
+
+    // Converges to well under a millimeter in a few rounds near the surface
+    let mut latitude = z.atan2(p * (1.0 - e2));
+    let mut altitude = 0.0;
+
+    for _ in 0..5 {
+        let prime_vertical = SEMI_MAJOR_AXIS / (1.0 - e2 * latitude.sin().powi(2)).sqrt();
+
+        // p / cos degenerates towards the poles, z / sin towards the equator
+        altitude = if latitude.cos().abs() > latitude.sin().abs() {
+            p / latitude.cos() - prime_vertical
+        } else {
+            z / latitude.sin() - prime_vertical * (1.0 - e2)
+        };
+
+        latitude = z.atan2(p * (1.0 - e2 * prime_vertical / (prime_vertical + altitude)));
+    }
+
+    return (latitude.to_degrees(), longitude.to_degrees(), altitude);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: &Position, b: &Position) {
+        let offset = a.to_enu(b);
+        let away = (offset[0].powi(2) + offset[1].powi(2) + offset[2].powi(2)).sqrt();
+
+        assert!(away < 0.001, "{:?} is {} m from {:?}", a, away, b);
+    }
+
+    #[test]
+    fn enu_round_trips() {
+        let origin = Position::new(51.4769, -0.0005, 45.0);
+
+        for enu in [
+            [0.0, 0.0, 0.0],
+            [120.5, -340.25, 3.0],
+            [-2500.0, 1800.0, -20.0],
+            [15_000.0, 15_000.0, 500.0],
+        ] {
+            let point = Position::from_enu(&origin, enu);
+            let back = point.to_enu(&origin);
+
+            for axis in 0..3 {
+                assert!(
+                    (back[axis] - enu[axis]).abs() < 0.001,
+                    "{:?} != {:?}",
+                    back,
+                    enu
+                );
+            }
+
+            assert_near(&Position::from_enu(&origin, point.to_enu(&origin)), &point);
+        }
+    }
+
+    #[test]
+    fn a_degree_of_latitude_is_about_111_km_north() {
+        let origin = Position::new(45.0, 7.0, 0.0);
+        let enu = Position::new(46.0, 7.0, 0.0).to_enu(&origin);
+
+        assert!(enu[0].abs() < 0.001);
+        assert!((enu[1] - 111_000.0).abs() < 500.0, "{:?}", enu);
+        // The earth curves away below the tangent plane
+        assert!(enu[2] < 0.0);
+    }
+
+    #[test]
+    fn converts_near_the_poles() {
+        for latitude in [89.9999, 90.0, -89.9999, -90.0] {
+            let origin = Position::new(latitude, 30.0, 120.0);
+
+            assert_near(&Position::from_enu(&origin, [0.0, 0.0, 0.0]), &origin);
+
+            let point = Position::from_enu(&origin, [3.0, -4.0, 5.0]);
+            let enu = point.to_enu(&origin);
+
+            assert!((enu[0] - 3.0).abs() < 0.001, "{:?}", enu);
+            assert!((enu[1] + 4.0).abs() < 0.001, "{:?}", enu);
+            assert!((enu[2] - 5.0).abs() < 0.001, "{:?}", enu);
+            assert!((point.altitude - 125.0).abs() < 0.01, "{}", point.altitude);
+        }
+
+        // Right on the axis there is no distance from it to divide
+        let polar_radius = SEMI_MAJOR_AXIS * (1.0 - FLATTENING);
+        let (latitude, _, altitude) = from_ecef([0.0, 0.0, polar_radius + 50.0]);
+
+        assert!((latitude - 90.0).abs() < 1e-9);
+        assert!((altitude - 50.0).abs() < 0.001, "{}", altitude);
+    }
+}
@@ -1,12 +1,90 @@
+use crate::server::data::mission::MissionProgress;
+use crate::server::data::position::Position;
+
 use serde::{Deserialize, Serialize};
 
+// Bump when fields are added. Cars from before versioning report 0.
+pub const TELEMENTRY_VERSION: u16 = 7;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ThrottleCapReason {
+    Latency,
+    PacketLoss,
+}
+
+// Optional fields are None when the car does not report them
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Telementry {
+    #[serde(default)]
+    pub version: u16,
+    // Kept for older cars, newer cars also fill position
     pub gps: [f32; 2],
+    #[serde(default)]
+    pub position: Option<Position>,
     pub heading: u16,
     pub cam_pos: [u8; 2],
     pub battery_charge: u8,
     pub speed: u8,
     pub latancy: u32,
     pub last_changed: i64,
+    // m/s^2 in the car frame: forward, right, up
+    #[serde(default)]
+    pub imu_acceleration: Option<[f32; 3]>,
+    // Amps
+    #[serde(default)]
+    pub motor_current: Option<f32>,
+    // Degrees celsius
+    #[serde(default)]
+    pub esc_temperature: Option<f32>,
+    // dBm
+    #[serde(default)]
+    pub signal_strength: Option<i16>,
+    // Set while the car has taken over from the driver, eg. flat battery
+    #[serde(default)]
+    pub failsafe: Option<bool>,
+    #[serde(default)]
+    pub estop: Option<bool>,
+    #[serde(default)]
+    pub firmware_version: Option<String>,
+    // Added in version 2
+    #[serde(default)]
+    pub mission: Option<MissionProgress>,
+    // Added in version 3
+    #[serde(default)]
+    pub returning_home: Option<bool>,
+    // Added in version 4, loss is the fraction of pings to the driver lost
+    #[serde(default)]
+    pub packet_loss: Option<f32>,
+    // Highest throttle the driver is allowed, 0.0 to 1.0
+    #[serde(default)]
+    pub throttle_cap: Option<f32>,
+    #[serde(default)]
+    pub throttle_cap_reason: Option<ThrottleCapReason>,
+    // Added in version 5, how much of the driver's command is being faded
+    // out over missing packets, 0.0 to 1.0
+    #[serde(default)]
+    pub concealment: Option<f32>,
+    // Control ticks so far that ran on a faded command
+    #[serde(default)]
+    pub concealed_ticks: Option<u64>,
+    // Added in version 6, meters to the nearest obstacle ahead
+    #[serde(default)]
+    pub obstacle_distance: Option<f32>,
+    // Set while automatic braking is holding the car back
+    #[serde(default)]
+    pub aeb_active: Option<bool>,
+    // Added in version 7, degrees between where the car points and where it
+    // is going, only sent by cars simulating tire slip
+    #[serde(default)]
+    pub slip_angle: Option<f32>,
+}
+
+impl Telementry {
+    // Position from whichever field the car reported
+    pub fn position(&self) -> Position {
+        match &self.position {
+            Some(p) => p.clone(),
+            None => Position::from_legacy(self.gps),
+        }
+    }
 }
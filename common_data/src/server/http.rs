@@ -1,3 +1,7 @@
+use crate::server::data::calibration::Calibration;
+use crate::server::data::capabilities::Capabilities;
+use crate::server::data::mission::Mission;
+use crate::server::data::telementry::Telementry;
 use crate::server::json::http::{
     AuthStartJson, AuthVerifyJson, Car, CreateCar, CreateCarReturn, GetCars,
 };
@@ -17,6 +21,13 @@ pub struct Http {
     auth_token: Option<String>,
 }
 
+#[derive(Clone)]
+pub struct CarHttp {
+    server_address: String,
+    car_id: String,
+    api_key: String,
+}
+
 pub struct AuthScope {
     valid_to: SystemTime,
     email: EmailAddress,
@@ -243,3 +254,155 @@ impl Http {
         }
     }
 }
+
+impl CarHttp {
+    pub fn new(server_address: String, car_id: String, api_key: String) -> CarHttp {
+        return CarHttp {
+            server_address,
+            car_id,
+            api_key,
+        };
+    }
+
+    pub async fn put_telementry(&self, telementry: &Telementry) -> Result<(), HttpErrors> {
+        let client = reqwest::Client::new();
+
+        let request_url = format!(
+            "{}/car/{}/telementry",
+            self.server_address.clone(),
+            self.car_id
+        );
+
+        let put_string = match serde_json::to_string(telementry) {
+            Ok(s) => s,
+            Err(_) => return Err(HttpErrors::EncodeError),
+        };
+
+        let res = match client
+            .put(request_url)
+            .body(put_string)
+            .header("Authorization", self.api_key.clone())
+            .header("Content-Type", "application/json")
+            .send()
+            .await
+        {
+            Ok(r) => r,
+            Err(_) => return Err(HttpErrors::ServerError),
+        };
+
+        match res.status().as_u16() {
+            200 => Ok(()),
+            400 => Err(HttpErrors::BadRequest),
+            401 => Err(HttpErrors::AuthError),
+            404 => Err(HttpErrors::NotFound),
+            _ => Err(HttpErrors::ServerError),
+        }
+    }
+
+    pub async fn get_mission(&self) -> Result<Option<Mission>, HttpErrors> {
+        let client = reqwest::Client::new();
+
+        let request_url = format!(
+            "{}/car/{}/mission",
+            self.server_address.clone(),
+            self.car_id
+        );
+
+        let res = match client
+            .get(request_url)
+            .header("Authorization", self.api_key.clone())
+            .send()
+            .await
+        {
+            Ok(r) => r,
+            Err(_) => return Err(HttpErrors::ServerError),
+        };
+
+        match res.status().as_u16() {
+            200 => (),
+            400 => return Err(HttpErrors::BadRequest),
+            401 => return Err(HttpErrors::AuthError),
+            404 => return Err(HttpErrors::NotFound),
+            _ => return Err(HttpErrors::ServerError),
+        };
+
+        match res.text().await {
+            Err(_) => Err(HttpErrors::ServerError),
+            Ok(t) => match serde_json::from_str(&t) {
+                Ok(o) => Ok(o),
+                Err(_) => Err(HttpErrors::DecodeError),
+            },
+        }
+    }
+
+    pub async fn get_calibration(&self) -> Result<Calibration, HttpErrors> {
+        let client = reqwest::Client::new();
+
+        let request_url = format!(
+            "{}/car/{}/calibration",
+            self.server_address.clone(),
+            self.car_id
+        );
+
+        let res = match client
+            .get(request_url)
+            .header("Authorization", self.api_key.clone())
+            .send()
+            .await
+        {
+            Ok(r) => r,
+            Err(_) => return Err(HttpErrors::ServerError),
+        };
+
+        match res.status().as_u16() {
+            200 => (),
+            400 => return Err(HttpErrors::BadRequest),
+            401 => return Err(HttpErrors::AuthError),
+            404 => return Err(HttpErrors::NotFound),
+            _ => return Err(HttpErrors::ServerError),
+        };
+
+        match res.text().await {
+            Err(_) => Err(HttpErrors::ServerError),
+            Ok(t) => match serde_json::from_str(&t) {
+                Ok(o) => Ok(o),
+                Err(_) => Err(HttpErrors::DecodeError),
+            },
+        }
+    }
+
+    pub async fn put_capabilities(&self, capabilities: &Capabilities) -> Result<(), HttpErrors> {
+        let client = reqwest::Client::new();
+
+        let request_url = format!(
+            "{}/car/{}/capabilities",
+            self.server_address.clone(),
+            self.car_id
+        );
+
+        let put_string = match serde_json::to_string(capabilities) {
+            Ok(s) => s,
+            Err(_) => return Err(HttpErrors::EncodeError),
+        };
+
+        let res = match client
+            .put(request_url)
+            .body(put_string)
+            .header("Authorization", self.api_key.clone())
+            .header("Content-Type", "application/json")
+            .send()
+            .await
+        {
+            Ok(r) => r,
+            Err(_) => return Err(HttpErrors::ServerError),
+        };
+
+        match res.status().as_u16() {
+            200 => Ok(()),
+            400 => Err(HttpErrors::BadRequest),
+            401 => Err(HttpErrors::AuthError),
+            404 => Err(HttpErrors::NotFound),
+            _ => Err(HttpErrors::ServerError),
+        }
+    }
+}
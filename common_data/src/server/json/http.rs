@@ -1,3 +1,7 @@
+use crate::server::data::capabilities::Capabilities;
+use crate::server::data::mission::Waypoint;
+use crate::server::data::telementry::ThrottleCapReason;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -22,6 +26,25 @@ pub struct Car {
     pub uuid: String,
     pub status: CarState,
     pub name: String,
+    #[serde(default)]
+    pub battery_charge: Option<u8>,
+    // Set when the last reported charge is at or below the server threshold
+    #[serde(default)]
+    pub low_battery: bool,
+    // Unset until the car has connected and reported them
+    #[serde(default)]
+    pub capabilities: Option<Capabilities>,
+    // Set while the car is holding the driver back because of a poor link
+    #[serde(default)]
+    pub throttle_cap: Option<f32>,
+    #[serde(default)]
+    pub throttle_cap_reason: Option<ThrottleCapReason>,
+    // Simulated by the server rather than a real car
+    #[serde(default)]
+    pub is_virtual: bool,
+    // Where drivers send control packets, only known for virtual cars
+    #[serde(default)]
+    pub control_address: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -32,6 +55,9 @@ pub struct GetCars {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateCar {
     pub name: String,
+    // The server simulates the car itself, for trying the rig without one
+    #[serde(default)]
+    pub is_virtual: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -40,3 +66,14 @@ pub struct CreateCarReturn {
     pub uuid: String,
     pub api_key: String,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateMission {
+    pub waypoints: Vec<Waypoint>,
+    #[serde(default = "default_laps")]
+    pub laps: u32,
+}
+
+fn default_laps() -> u32 {
+    return 1;
+}
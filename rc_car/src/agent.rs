@@ -0,0 +1,637 @@
+use crate::control::aeb::Aeb;
+use crate::control::battery::{BatteryLimiter, BatteryState};
+use crate::control::concealment::Concealment;
+use crate::control::cruise::CruiseControl;
+use crate::control::estimator::PoseEstimator;
+use crate::control::governor::Governor;
+use crate::control::home::{ReturnHome, ReturnHomeReason};
+use crate::control::laps::LapTimer;
+use crate::control::mission::MissionRunner;
+use crate::data::config::CarConfig;
+use crate::sim::vehicle::GRAVITY;
+use crate::sim::Simulation;
+
+use common_data::commands::estop::{self, EStop};
+use common_data::commands::motion::Motion;
+use common_data::commands::movement::{self, Movement};
+use common_data::commands::ping::{self, Ping};
+use common_data::commands::status::Status;
+use common_data::server::data::calibration::Calibration;
+use common_data::server::data::capabilities::{Capabilities, Sensor};
+use common_data::server::data::mission::Mission;
+use common_data::server::data::position::FixType;
+use common_data::server::data::telementry::{Telementry, TELEMENTRY_VERSION};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct Agent {
+    pub config: CarConfig,
+    pub calibration: Calibration,
+    pub sim: Simulation,
+    pub aeb: Aeb,
+    pub battery_limiter: BatteryLimiter,
+    pub concealment: Concealment,
+    pub cruise: CruiseControl,
+    pub estimator: PoseEstimator,
+    pub governor: Governor,
+    pub laps: LapTimer,
+    pub mission: Option<MissionRunner>,
+    pub return_home: ReturnHome,
+    pub command: Movement,
+    pub estop: bool,
+    // Seconds of simulated time since the agent started
+    pub sim_time: f64,
+    // Seconds since the last control packet
+    pub since_command: f32,
+    // Set once a driver has connected, link loss needs a link first
+    link_seen: bool,
+    battery_failsafe: bool,
+    // Packets through the downlink for main to send to the driver
+    outgoing: Vec<Vec<u8>>,
+    since_motion: f32,
+    motion_sequence: u16,
+    since_status: f32,
+    status_sequence: u16,
+    // Milliseconds since the epoch that sim_time counts from
+    started_at: i64,
+}
+
+impl Agent {
+    pub fn new(config: CarConfig) -> Self {
+        let started_at = match config.start_time {
+            Some(t) => t,
+            None => match SystemTime::now().duration_since(UNIX_EPOCH) {
+                Ok(d) => d.as_millis() as i64,
+                Err(_) => 0,
+            },
+        };
+
+        return Agent {
+            sim: Simulation::new(config.sim.clone()),
+            aeb: Aeb::new(config.aeb.clone()),
+            battery_limiter: BatteryLimiter::new(config.battery_limits.clone()),
+            calibration: Calibration::default(),
+            concealment: Concealment::new(config.concealment.clone()),
+            cruise: CruiseControl::new(config.cruise.clone()),
+            estimator: PoseEstimator::new(config.estimator.clone()),
+            governor: Governor::new(config.governor.clone()),
+            laps: LapTimer::new(config.laps.clone(), &config.sim.world),
+            mission: None,
+            return_home: ReturnHome::new(config.return_home.clone()),
+            command: Movement::new(),
+            estop: false,
+            sim_time: 0.0,
+            since_command: 0.0,
+            link_seen: false,
+            battery_failsafe: false,
+            outgoing: Vec::new(),
+            since_motion: 0.0,
+            motion_sequence: 0,
+            since_status: 0.0,
+            status_sequence: 0,
+            started_at,
+            config,
+        };
+    }
+
+    // Passes a control packet from the driver through the uplink, it is
+    // applied once it arrives
+    pub fn handle_packet(&mut self, packet: &[u8]) {
+        self.sim.uplink.send(packet);
+        self.receive_packets();
+    }
+
+    fn receive_packets(&mut self) {
+        for packet in self.sim.uplink.receive() {
+            self.apply_packet(&packet);
+        }
+    }
+
+    // Decodes a control packet and applies it, bad packets are dropped
+    fn apply_packet(&mut self, packet: &[u8]) {
+        match (packet.first(), packet.len()) {
+            (Some(&movement::COMMAND_NUMBER), 5) => {
+                let packet: [u8; 5] = [packet[0], packet[1], packet[2], packet[3], packet[4]];
+
+                if let Ok(m) = Movement::decode_packet(packet) {
+                    self.set_command(m);
+                }
+            }
+            // The driver echoing a ping back
+            (Some(&ping::COMMAND_NUMBER), 5) => {
+                let packet: [u8; 5] = [packet[0], packet[1], packet[2], packet[3], packet[4]];
+
+                if let Ok(p) = Ping::decode_packet(packet) {
+                    self.governor.handle_pong(p.sequence);
+                }
+            }
+            (Some(&estop::COMMAND_NUMBER), 4) => {
+                let packet: [u8; 4] = [packet[0], packet[1], packet[2], packet[3]];
+
+                if let Ok(e) = EStop::decode_packet(packet) {
+                    self.set_estop(e.engaged);
+                }
+            }
+            _ => (),
+        };
+    }
+
+    pub fn set_command(&mut self, movement: Movement) {
+        self.since_command = 0.0;
+        self.link_seen = true;
+
+        // Any manual input takes over from the autopilot. Going home on a flat
+        // battery can only be stopped with the E-stop.
+        if movement.movement_command != [0, 0] {
+            self.abort_mission();
+
+            if self.return_home.reason == Some(ReturnHomeReason::LinkLoss) {
+                self.return_home.cancel();
+            }
+        }
+
+        self.command = movement;
+    }
+
+    pub fn set_estop(&mut self, engaged: bool) {
+        self.since_command = 0.0;
+        self.link_seen = true;
+
+        if engaged {
+            self.abort_mission();
+            self.return_home.cancel();
+            self.command = Movement::new();
+        }
+
+        self.estop = engaged;
+    }
+
+    // Returns false when the car cannot start the mission
+    pub fn start_mission(&mut self, mission: Mission) -> bool {
+        if self.estop {
+            return false;
+        }
+
+        let start = match self.estimator.pose() {
+            Some(p) => [p.east, p.north],
+            None => return false,
+        };
+
+        // Missions run without a driver, so quiet control links are expected
+        self.link_seen = false;
+        self.return_home.cancel();
+        self.command = Movement::new();
+        self.mission = Some(MissionRunner::new(
+            self.config.mission.clone(),
+            mission,
+            &self.sim.origin(),
+            start,
+        ));
+
+        return true;
+    }
+
+    pub fn abort_mission(&mut self) {
+        if let Some(mission) = self.mission.as_mut() {
+            mission.abort();
+        }
+    }
+
+    pub fn mission_running(&self) -> bool {
+        match &self.mission {
+            Some(m) => m.is_running(),
+            None => false,
+        }
+    }
+
+    fn start_return_home(&mut self, reason: ReturnHomeReason) -> bool {
+        if self.estop {
+            return false;
+        }
+
+        let start = match self.estimator.pose() {
+            Some(p) => [p.east, p.north],
+            None => return false,
+        };
+
+        let started =
+            self.return_home
+                .start(reason, &self.config.mission, &self.sim.origin(), start);
+
+        // Going home takes over from whatever mission was running
+        if started {
+            self.abort_mission();
+        }
+
+        return started;
+    }
+
+    pub fn tick(&mut self, dt: f32) {
+        self.sim.uplink.step(dt);
+        self.sim.downlink.step(dt);
+        self.receive_packets();
+
+        self.sim_time += f64::from(dt);
+        self.since_command += dt;
+
+        // Watchdog, stop acting on a command from a driver that went quiet
+        if self.since_command > self.config.command_timeout {
+            self.command = Movement::new();
+        }
+
+        if let Some(pose) = self.estimator.pose() {
+            self.return_home
+                .set_home_if_unset(self.sim.local_to_position(pose.east, pose.north));
+        }
+
+        let battery_state = self
+            .battery_limiter
+            .update(self.sim.battery.charge_percent());
+
+        if battery_state == BatteryState::Critical && !self.battery_failsafe {
+            self.battery_failsafe = true;
+            self.command = Movement::new();
+            self.abort_mission();
+            self.start_return_home(ReturnHomeReason::Battery);
+        }
+
+        if self.link_seen
+            && self.since_command > self.return_home.config.link_loss_timeout
+            && !self.return_home.is_active()
+        {
+            self.link_seen = false;
+            self.start_return_home(ReturnHomeReason::LinkLoss);
+        }
+
+        // Between a dropped packet and the watchdog, fade the last command out
+        // rather than holding it or cutting it
+        let held = if self.command.movement_command != [0, 0] {
+            self.concealment.update(self.since_command)
+        } else {
+            self.concealment.update(0.0)
+        };
+
+        let mut throttle = f32::from(self.command.movement_command[0]) / 100.0 * held;
+        let mut steering = f32::from(self.command.movement_command[1]) / 100.0 * held;
+
+        // Only pings while someone is driving, a quiet link is for the
+        // watchdog and return home to deal with
+        let connected = self.link_seen && self.since_command <= self.config.command_timeout;
+        if let Some(mut p) = self.governor.update(connected, dt) {
+            self.sim.downlink.send(&p.generate_packet());
+        }
+
+        if self.cruise.config.enabled {
+            let measured_speed = match self.estimator.pose() {
+                Some(pose) => pose.speed as f32,
+                None => self.sim.sensors.readings.wheel_speed.unwrap_or(0.0),
+            };
+
+            let vehicle_config = &self.sim.vehicle.config;
+            throttle = self.cruise.update(
+                throttle,
+                measured_speed,
+                vehicle_config.max_speed,
+                vehicle_config.max_reverse_speed,
+                dt,
+            );
+        }
+
+        if let Some(pose) = self.estimator.pose() {
+            if let Some(mission) = self.mission.as_mut() {
+                if mission.is_running() {
+                    (throttle, steering) = mission.update(&pose, &self.sim.vehicle);
+                }
+            }
+
+            if self.return_home.is_active() {
+                (throttle, steering) = self.return_home.update(&pose, &self.sim.vehicle);
+            }
+        } else if self.mission_running() || self.return_home.is_active() {
+            // Lost the position, wait for it to come back
+            throttle = 0.0;
+            steering = 0.0;
+        }
+
+        // The autopilot is on the car, only the driver is slowed by the link
+        if !self.mission_running() && !self.return_home.is_active() {
+            throttle = self.governor.limit_throttle(throttle);
+        }
+
+        if self.estop {
+            throttle = 0.0;
+            steering = 0.0;
+        }
+
+        // Start the speed loop fresh when the driver gets control back
+        if self.estop || self.mission_running() || self.return_home.is_active() {
+            self.cruise.reset();
+        }
+
+        let ranges: Vec<(f32, Option<f32>)> = self
+            .sim
+            .sensors
+            .range
+            .iter()
+            .map(|(c, _)| c.angle)
+            .zip(self.sim.sensors.readings.ranges.iter().copied())
+            .collect();
+        let throttle =
+            self.aeb
+                .limit_throttle(throttle, &ranges, self.sim.vehicle.config.max_speed);
+
+        // Going home already runs at a reduced speed and has to be allowed to
+        // drive on a critical battery
+        let throttle = if self.return_home.is_active() {
+            throttle
+        } else {
+            self.battery_limiter
+                .limit_throttle(throttle, self.sim.vehicle.config.max_speed)
+        };
+
+        // Trim and limits correct the servo and ESC, the estimator still
+        // models the steering that was asked for
+        let (output_throttle, output_steering) = self.calibration.apply(throttle, steering);
+
+        self.sim.step(output_throttle, output_steering, dt);
+
+        self.estimator.update(
+            &self.sim.sensors.readings,
+            self.sim.vehicle.model.as_ref(),
+            steering.clamp(-1.0, 1.0),
+            dt,
+        );
+
+        self.laps
+            .update(self.estimator.pose().map(|p| [p.east, p.north]), dt);
+
+        self.stream_motion(connected, dt);
+        self.stream_status(connected, dt);
+        self.outgoing.extend(self.sim.downlink.receive());
+    }
+
+    // Sends the driver's rig how the car is moving
+    fn stream_motion(&mut self, connected: bool, dt: f32) {
+        if !stream_due(
+            &mut self.since_motion,
+            connected,
+            self.config.motion_rate,
+            dt,
+        ) {
+            if !connected {
+                self.sim.vehicle.state.impact = [0.0, 0.0];
+            }
+            return;
+        }
+
+        let impact = std::mem::take(&mut self.sim.vehicle.state.impact);
+        let state = &self.sim.vehicle.state;
+
+        // The sim has no gyro, the yaw rate is the true one
+        let acceleration = match self.sim.sensors.readings.acceleration {
+            Some(a) => a,
+            None => [state.acceleration[0], state.acceleration[1], GRAVITY],
+        };
+
+        let mut motion = Motion::new(
+            self.motion_sequence,
+            state.speed,
+            acceleration,
+            state.yaw_rate,
+            state.steering_angle,
+            impact,
+        );
+        self.motion_sequence = self.motion_sequence.wrapping_add(1);
+
+        self.sim.downlink.send(&motion.generate_packet());
+    }
+
+    // Sends the driver's rig what its dashboard shows
+    fn stream_status(&mut self, connected: bool, dt: f32) {
+        if !stream_due(
+            &mut self.since_status,
+            connected,
+            self.config.status_rate,
+            dt,
+        ) {
+            return;
+        }
+
+        let readings = &self.sim.sensors.readings;
+
+        // Same as telementry, the filtered pose once there is one
+        let (speed, heading) = match self.estimator.pose() {
+            Some(p) if self.estimator.config.enabled => {
+                (p.speed as f32, p.heading.to_degrees() as f32)
+            }
+            _ => (
+                readings.wheel_speed.unwrap_or(0.0),
+                readings.heading.unwrap_or(0.0),
+            ),
+        };
+
+        let mut status = Status {
+            sequence: self.status_sequence,
+            speed,
+            battery_charge: self.sim.battery.charge_percent(),
+            heading: heading.rem_euclid(360.0),
+            latency: self
+                .governor
+                .rtt
+                .map(|r| r.round().min(f32::from(u16::MAX)) as u16)
+                .unwrap_or(0),
+            laps: self.laps.laps,
+            lap_time: self.laps.lap_time.unwrap_or(0.0) as f32,
+            last_lap: self.laps.last_lap.unwrap_or(0.0) as f32,
+            best_lap: self.laps.best_lap.unwrap_or(0.0) as f32,
+            ..Status::default()
+        };
+        self.status_sequence = self.status_sequence.wrapping_add(1);
+
+        self.sim.downlink.send(&status.generate_packet());
+    }
+
+    pub fn telementry(&self) -> Telementry {
+        let readings = &self.sim.sensors.readings;
+
+        let mut position = match readings.gps {
+            Some(g) => self.sim.local_to_position(g[0], g[1]),
+            None => self.sim.origin(),
+        };
+
+        // The sim has no altitude so the best it can report is a 2D fix
+        position.fix = if readings.gps_valid {
+            FixType::Fix2D
+        } else {
+            FixType::NoFix
+        };
+        position.horizontal_accuracy = self.estimator.config.gps_noise as f32;
+
+        let mut heading = readings.heading.unwrap_or(0.0);
+        let mut speed = readings.wheel_speed.unwrap_or(0.0);
+
+        // Prefer the filtered pose once the estimator has a fix
+        if self.estimator.config.enabled {
+            if let Some(pose) = self.estimator.pose() {
+                let fix = position.fix;
+
+                position = self.sim.local_to_position(pose.east, pose.north);
+                position.fix = fix;
+                position.horizontal_accuracy = self.estimator.position_accuracy() as f32;
+
+                heading = pose.heading.to_degrees() as f32;
+                speed = pose.speed as f32;
+            }
+        }
+
+        let gps = match readings.gps {
+            Some(_) => position.to_legacy(),
+            None => [0.0, 0.0],
+        };
+
+        // Sim time rather than the wall clock so repeated runs match exactly
+        let last_changed = self.started_at + (self.sim_time * 1000.0).round() as i64;
+
+        return Telementry {
+            version: TELEMENTRY_VERSION,
+            gps,
+            position: Some(position),
+            heading: (heading.rem_euclid(360.0).round() as u16) % 360,
+            cam_pos: [0, 0],
+            battery_charge: self.sim.battery.charge_percent(),
+            // km/h
+            speed: (speed.abs() * 3.6).round().min(255.0) as u8,
+            latancy: self.governor.rtt.map(|r| r.round() as u32).unwrap_or(0),
+            last_changed,
+            imu_acceleration: readings.acceleration,
+            motor_current: Some(self.sim.battery.motor_current_ma / 1000.0),
+            esc_temperature: Some(self.sim.esc.temperature),
+            signal_strength: Some(self.sim.radio.signal_strength.round() as i16),
+            failsafe: Some(
+                self.battery_limiter.state == BatteryState::Critical
+                    || self.return_home.is_active(),
+            ),
+            estop: Some(self.estop),
+            firmware_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            mission: self.mission.as_ref().map(|m| m.progress()),
+            returning_home: Some(self.return_home.is_active()),
+            packet_loss: Some(self.governor.packet_loss),
+            throttle_cap: Some(self.governor.throttle_cap),
+            throttle_cap_reason: self.governor.reason,
+            concealment: Some(self.concealment.amount),
+            concealed_ticks: Some(self.concealment.concealed_ticks),
+            obstacle_distance: self.aeb.obstacle_distance,
+            aeb_active: Some(self.aeb.active),
+            slip_angle: if self.sim.vehicle.model.slips() {
+                Some(self.sim.vehicle.state.slip_angle.to_degrees())
+            } else {
+                None
+            },
+        };
+    }
+
+    pub fn take_packets(&mut self) -> Vec<Vec<u8>> {
+        return std::mem::take(&mut self.outgoing);
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        let sensor_config = &self.sim.config.sensors;
+
+        let mut sensors = Vec::new();
+
+        if sensor_config.gps.rate_hz > 0.0 {
+            sensors.push(Sensor::Gps);
+        }
+        if sensor_config.heading.rate_hz > 0.0 {
+            sensors.push(Sensor::Heading);
+        }
+        if sensor_config.wheel_speed.rate_hz > 0.0 {
+            sensors.push(Sensor::WheelSpeed);
+        }
+        if sensor_config.accelerometer.rate_hz > 0.0 {
+            sensors.push(Sensor::Accelerometer);
+        }
+        if !sensor_config.range.is_empty() {
+            sensors.push(Sensor::Range);
+        }
+
+        return Capabilities {
+            commands: vec![
+                movement::COMMAND_NUMBER,
+                estop::COMMAND_NUMBER,
+                ping::COMMAND_NUMBER,
+            ],
+            axes: self.sim.vehicle.model.axes(),
+            camera_gimbal: self.config.camera_gimbal.clone(),
+            max_speed: self.sim.vehicle.config.max_speed,
+            sensors,
+        };
+    }
+}
+
+// Whether a stream of rate packets a second sends on this tick. Sends on the
+// tick nearest each interval so the average rate is right.
+fn stream_due(since: &mut f32, connected: bool, rate: f32, dt: f32) -> bool {
+    if !connected || rate <= 0.0 {
+        *since = 0.0;
+        return false;
+    }
+
+    let interval = 1.0 / rate;
+    *since += dt;
+
+    if *since < interval - dt / 2.0 {
+        return false;
+    }
+
+    *since = (*since - interval).min(interval);
+
+    return true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_data::server::data::mission::Waypoint;
+
+    #[test]
+    fn link_loss_home_takes_over_from_a_mission() {
+        let config = CarConfig {
+            start_time: Some(0),
+            ..CarConfig::default()
+        };
+        let dt = 1.0 / config.tick_rate as f32;
+        let mut agent = Agent::new(config);
+
+        // Long enough for the estimator to settle on a pose
+        for _ in 0..agent.config.tick_rate * 2 {
+            agent.tick(dt);
+        }
+
+        let far = agent.sim.local_to_position(0.0, 500.0);
+        let mission = Mission {
+            uuid: "far".to_string(),
+            waypoints: vec![Waypoint {
+                latitude: far.latitude,
+                longitude: far.longitude,
+                speed: None,
+            }],
+            laps: 1,
+        };
+        assert!(agent.start_mission(mission));
+
+        // A neutral keep-alive leaves the mission running but arms link loss
+        let mut neutral = Movement::new();
+        let _ = neutral.set_accelerate(0);
+        agent.handle_packet(&neutral.generate_packet());
+        assert!(agent.mission_running());
+
+        let quiet = agent.return_home.config.link_loss_timeout + 1.0;
+        for _ in 0..(quiet / dt) as u32 {
+            agent.tick(dt);
+        }
+
+        assert!(agent.return_home.is_active());
+        assert!(!agent.mission_running());
+    }
+}
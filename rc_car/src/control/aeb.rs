@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AebConfig {
+    pub enabled: bool,
+    // Braking the car can count on, m/s^2
+    pub deceleration: f32,
+    // Seconds before braking takes effect
+    pub reaction_time: f32,
+    // Meters to stop short of the obstacle
+    pub margin: f32,
+    // Only range sensors within this many degrees of straight ahead are used
+    pub cone: f32,
+}
+
+impl Default for AebConfig {
+    fn default() -> Self {
+        return AebConfig {
+            enabled: false,
+            deceleration: 3.0,
+            reaction_time: 0.1,
+            margin: 0.5,
+            cone: 30.0,
+        };
+    }
+}
+
+// Automatic emergency braking, holds the car to a speed it can still stop
+// from before the nearest obstacle ahead
+#[derive(Debug, Clone)]
+pub struct Aeb {
+    pub config: AebConfig,
+    // Nearest obstacle ahead in meters
+    pub obstacle_distance: Option<f32>,
+    // Set while the throttle is being cut back
+    pub active: bool,
+}
+
+impl Aeb {
+    pub fn new(config: AebConfig) -> Self {
+        return Aeb {
+            config,
+            obstacle_distance: None,
+            active: false,
+        };
+    }
+
+    // ranges are (degrees from straight ahead, meters) for each range sensor.
+    // Reversing is left alone, the sensors only look forward.
+    pub fn limit_throttle(
+        &mut self,
+        throttle: f32,
+        ranges: &[(f32, Option<f32>)],
+        max_speed: f32,
+    ) -> f32 {
+        self.obstacle_distance = ranges
+            .iter()
+            .filter(|(angle, _)| angle.abs() <= self.config.cone)
+            .filter_map(|(_, range)| *range)
+            .reduce(f32::min);
+
+        self.active = false;
+
+        if !self.config.enabled || throttle <= 0.0 || max_speed <= 0.0 {
+            return throttle;
+        }
+
+        let distance = match self.obstacle_distance {
+            Some(d) => d,
+            None => return throttle,
+        };
+
+        // Fastest speed v with v * reaction + v^2 / 2a + margin <= distance
+        let room = distance - self.config.margin;
+        let allowed_speed = if room <= 0.0 {
+            0.0
+        } else {
+            let deceleration = self.config.deceleration.max(0.1);
+            let reaction = self.config.reaction_time.max(0.0) * deceleration;
+
+            -reaction + (reaction.powi(2) + 2.0 * deceleration * room).sqrt()
+        };
+
+        let cap = (allowed_speed / max_speed).clamp(0.0, 1.0);
+
+        if throttle > cap {
+            self.active = true;
+            return cap;
+        }
+
+        return throttle;
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BatteryLimitConfig {
+    // Below this charge the car is held to low_speed_cap
+    pub low_percent: u8,
+    // Below this charge the car will not drive at all
+    pub critical_percent: u8,
+    // Speed cap in m/s while the battery is low
+    pub low_speed_cap: f32,
+}
+
+impl Default for BatteryLimitConfig {
+    fn default() -> Self {
+        return BatteryLimitConfig {
+            low_percent: 25,
+            critical_percent: 10,
+            low_speed_cap: 2.0,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatteryState {
+    Normal,
+    Low,
+    Critical,
+}
+
+#[derive(Debug, Clone)]
+pub struct BatteryLimiter {
+    pub config: BatteryLimitConfig,
+    pub state: BatteryState,
+}
+
+impl BatteryLimiter {
+    pub fn new(config: BatteryLimitConfig) -> Self {
+        return BatteryLimiter {
+            config,
+            state: BatteryState::Normal,
+        };
+    }
+
+    pub fn update(&mut self, charge_percent: u8) -> BatteryState {
+        self.state = if charge_percent <= self.config.critical_percent {
+            BatteryState::Critical
+        } else if charge_percent <= self.config.low_percent {
+            BatteryState::Low
+        } else {
+            BatteryState::Normal
+        };
+
+        return self.state;
+    }
+
+    // The car tops out at throttle * max_speed, so scaling the throttle down
+    // caps the speed
+    pub fn limit_throttle(&self, throttle: f32, max_speed: f32) -> f32 {
+        match self.state {
+            BatteryState::Normal => throttle,
+            BatteryState::Critical => 0.0,
+            BatteryState::Low => {
+                if max_speed <= 0.0 {
+                    return 0.0;
+                }
+
+                let cap = (self.config.low_speed_cap / max_speed).clamp(0.0, 1.0);
+
+                return throttle.clamp(-cap, cap);
+            }
+        }
+    }
+}
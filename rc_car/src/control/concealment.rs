@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ConcealmentConfig {
+    pub enabled: bool,
+    // Seconds the last command is held unchanged after packets stop
+    pub hold_time: f32,
+    // Seconds after the hold to fade the command to neutral. The watchdog
+    // still stops the car at command_timeout if that comes first.
+    pub decay_time: f32,
+}
+
+impl Default for ConcealmentConfig {
+    fn default() -> Self {
+        return ConcealmentConfig {
+            enabled: true,
+            hold_time: 0.15,
+            decay_time: 0.25,
+        };
+    }
+}
+
+// Smooths over dropped control packets by holding, then fading out, the last
+// command the driver sent
+#[derive(Debug, Clone)]
+pub struct Concealment {
+    pub config: ConcealmentConfig,
+    // How much of the command has been faded out, 0.0 to 1.0
+    pub amount: f32,
+    // Ticks where some of the command was faded out
+    pub concealed_ticks: u64,
+}
+
+impl Concealment {
+    pub fn new(config: ConcealmentConfig) -> Self {
+        return Concealment {
+            config,
+            amount: 0.0,
+            concealed_ticks: 0,
+        };
+    }
+
+    // since_command is seconds since the last control packet. Returns the
+    // factor to scale the held command by.
+    pub fn update(&mut self, since_command: f32) -> f32 {
+        if !self.config.enabled {
+            self.amount = 0.0;
+            return 1.0;
+        }
+
+        let faded = since_command - self.config.hold_time;
+
+        self.amount = if faded <= 0.0 {
+            0.0
+        } else if self.config.decay_time <= 0.0 {
+            1.0
+        } else {
+            (faded / self.config.decay_time).clamp(0.0, 1.0)
+        };
+
+        if self.amount > 0.0 {
+            self.concealed_ticks += 1;
+        }
+
+        return 1.0 - self.amount;
+    }
+}
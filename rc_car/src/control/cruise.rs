@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CruiseConfig {
+    // When enabled the accelerate percent is a target speed, a percentage of
+    // max_speed forward or max_reverse_speed backward
+    pub enabled: bool,
+    // Throttle per m/s of speed error
+    pub kp: f32,
+    // Throttle per m/s of error built up over one second
+    pub ki: f32,
+    // Throttle per m/s^2 of change in measured speed
+    pub kd: f32,
+    // Largest throttle the integral term can add or remove
+    pub integral_limit: f32,
+}
+
+impl Default for CruiseConfig {
+    fn default() -> Self {
+        return CruiseConfig {
+            enabled: false,
+            kp: 0.15,
+            ki: 0.05,
+            kd: 0.0,
+            integral_limit: 0.3,
+        };
+    }
+}
+
+// Speed PID with the target speed as feed forward, so the gains only have to
+// correct for what the open loop gets wrong
+#[derive(Debug, Clone)]
+pub struct CruiseControl {
+    pub config: CruiseConfig,
+    pub target_speed: f32,
+    integral: f32,
+    last_speed: Option<f32>,
+}
+
+impl CruiseControl {
+    pub fn new(config: CruiseConfig) -> Self {
+        return CruiseControl {
+            config,
+            target_speed: 0.0,
+            integral: 0.0,
+            last_speed: None,
+        };
+    }
+
+    pub fn reset(&mut self) {
+        self.target_speed = 0.0;
+        self.integral = 0.0;
+        self.last_speed = None;
+    }
+
+    // percent is the driver's accelerate value, -1.0 to 1.0. Returns throttle
+    // in the same range.
+    pub fn update(
+        &mut self,
+        percent: f32,
+        measured_speed: f32,
+        max_speed: f32,
+        max_reverse_speed: f32,
+        dt: f32,
+    ) -> f32 {
+        let percent = percent.clamp(-1.0, 1.0);
+
+        self.target_speed = if percent >= 0.0 {
+            percent * max_speed
+        } else {
+            percent * max_reverse_speed
+        };
+
+        // Let go rather than hold the brakes on at a standstill
+        if percent == 0.0 && measured_speed.abs() < 0.1 {
+            self.integral = 0.0;
+            self.last_speed = Some(measured_speed);
+            return 0.0;
+        }
+
+        let feed_forward = if self.target_speed >= 0.0 && max_speed > 0.0 {
+            self.target_speed / max_speed
+        } else if self.target_speed < 0.0 && max_reverse_speed > 0.0 {
+            self.target_speed / max_reverse_speed
+        } else {
+            0.0
+        };
+
+        let error = self.target_speed - measured_speed;
+
+        let derivative = match self.last_speed {
+            Some(last) if dt > 0.0 => (measured_speed - last) / dt,
+            _ => 0.0,
+        };
+        self.last_speed = Some(measured_speed);
+
+        let unclamped =
+            feed_forward + self.config.kp * error + self.integral - self.config.kd * derivative;
+        let throttle = unclamped.clamp(-1.0, 1.0);
+
+        // Stop winding up while the throttle is already pinned
+        if throttle == unclamped || unclamped.signum() != error.signum() {
+            let limit = self.config.integral_limit.abs();
+            self.integral = (self.integral + self.config.ki * error * dt).clamp(-limit, limit);
+        }
+
+        return throttle;
+    }
+}
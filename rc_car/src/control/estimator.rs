@@ -0,0 +1,248 @@
+use crate::sim::sensors::SensorReadings;
+use crate::sim::vehicle::VehicleModel;
+
+use serde::{Deserialize, Serialize};
+
+use std::f64::consts::{PI, TAU};
+
+// State index order: east, north, heading (radians clockwise from north), speed
+const EAST: usize = 0;
+const NORTH: usize = 1;
+const HEADING: usize = 2;
+const SPEED: usize = 3;
+
+type Matrix = [[f64; 4]; 4];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct EstimatorConfig {
+    pub enabled: bool,
+    // Process noise as the standard deviation built up over one second
+    pub position_process_noise: f64,
+    pub heading_process_noise: f64,
+    pub speed_process_noise: f64,
+    // Measurement noise standard deviations of the sensors on the car
+    pub gps_noise: f64,
+    pub heading_noise: f64,
+    pub wheel_speed_noise: f64,
+}
+
+impl Default for EstimatorConfig {
+    fn default() -> Self {
+        return EstimatorConfig {
+            enabled: true,
+            position_process_noise: 0.5,
+            heading_process_noise: 5.0_f64.to_radians(),
+            speed_process_noise: 2.0,
+            gps_noise: 2.0,
+            heading_noise: 3.0_f64.to_radians(),
+            wheel_speed_noise: 0.1,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Pose {
+    pub east: f64,
+    pub north: f64,
+    // Radians clockwise from north
+    pub heading: f64,
+    pub speed: f64,
+}
+
+// Extended Kalman filter fusing GPS, heading and wheel speed. Steering is used
+// as the control input to predict the turn between measurements.
+#[derive(Debug, Clone)]
+pub struct PoseEstimator {
+    pub config: EstimatorConfig,
+    state: [f64; 4],
+    covariance: Matrix,
+    initialised: bool,
+}
+
+impl PoseEstimator {
+    pub fn new(config: EstimatorConfig) -> Self {
+        return PoseEstimator {
+            config,
+            state: [0.0; 4],
+            covariance: [[0.0; 4]; 4],
+            initialised: false,
+        };
+    }
+
+    pub fn pose(&self) -> Option<Pose> {
+        if !self.initialised {
+            return None;
+        }
+
+        return Some(Pose {
+            east: self.state[EAST],
+            north: self.state[NORTH],
+            heading: self.state[HEADING],
+            speed: self.state[SPEED],
+        });
+    }
+
+    // One standard deviation of the position estimate in meters
+    pub fn position_accuracy(&self) -> f64 {
+        let variance = self.covariance[EAST][EAST].max(self.covariance[NORTH][NORTH]);
+
+        return variance.max(0.0).sqrt();
+    }
+
+    // model predicts the turn the vehicle makes for the steering command, -1.0
+    // to 1.0, that was applied this tick
+    pub fn update(
+        &mut self,
+        readings: &SensorReadings,
+        model: &dyn VehicleModel,
+        steering: f32,
+        dt: f32,
+    ) {
+        if !self.initialised {
+            self.initialise(readings);
+            return;
+        }
+
+        self.predict(model, steering, f64::from(dt));
+
+        if readings.gps_updated && readings.gps_valid {
+            if let Some(gps) = readings.gps {
+                let variance = self.config.gps_noise.powi(2);
+                self.correct(EAST, gps[0] - self.state[EAST], variance);
+                self.correct(NORTH, gps[1] - self.state[NORTH], variance);
+            }
+        }
+
+        if readings.heading_updated {
+            if let Some(heading) = readings.heading {
+                let residual = wrap_angle(f64::from(heading).to_radians() - self.state[HEADING]);
+                self.correct(HEADING, residual, self.config.heading_noise.powi(2));
+                self.state[HEADING] = self.state[HEADING].rem_euclid(TAU);
+            }
+        }
+
+        if readings.wheel_speed_updated {
+            if let Some(speed) = readings.wheel_speed {
+                let residual = f64::from(speed) - self.state[SPEED];
+                self.correct(SPEED, residual, self.config.wheel_speed_noise.powi(2));
+            }
+        }
+    }
+
+    fn initialise(&mut self, readings: &SensorReadings) {
+        let gps = match readings.gps {
+            Some(g) => g,
+            None => return,
+        };
+
+        let heading = match readings.heading {
+            Some(h) => f64::from(h).to_radians(),
+            None => return,
+        };
+
+        self.state = [
+            gps[0],
+            gps[1],
+            heading,
+            f64::from(readings.wheel_speed.unwrap_or(0.0)),
+        ];
+
+        self.covariance = [[0.0; 4]; 4];
+        self.covariance[EAST][EAST] = self.config.gps_noise.powi(2);
+        self.covariance[NORTH][NORTH] = self.config.gps_noise.powi(2);
+        self.covariance[HEADING][HEADING] = self.config.heading_noise.powi(2);
+        self.covariance[SPEED][SPEED] = self.config.wheel_speed_noise.powi(2);
+
+        self.initialised = true;
+    }
+
+    fn predict(&mut self, model: &dyn VehicleModel, steering: f32, dt: f64) {
+        let heading = self.state[HEADING];
+        let speed = self.state[SPEED];
+
+        let yaw_rate = f64::from(model.yaw_rate(speed as f32, steering));
+        // How much faster it turns for a little more speed
+        let turn_rate =
+            (f64::from(model.yaw_rate(speed as f32 + 0.01, steering)) - yaw_rate) / 0.01;
+
+        self.state[EAST] += speed * heading.sin() * dt;
+        self.state[NORTH] += speed * heading.cos() * dt;
+        self.state[HEADING] = (heading + yaw_rate * dt).rem_euclid(TAU);
+
+        // Jacobian of the motion model
+        let mut jacobian: Matrix = identity();
+        jacobian[EAST][HEADING] = speed * heading.cos() * dt;
+        jacobian[EAST][SPEED] = heading.sin() * dt;
+        jacobian[NORTH][HEADING] = -speed * heading.sin() * dt;
+        jacobian[NORTH][SPEED] = heading.cos() * dt;
+        jacobian[HEADING][SPEED] = turn_rate * dt;
+
+        let mut covariance = multiply(
+            &multiply(&jacobian, &self.covariance),
+            &transpose(&jacobian),
+        );
+
+        covariance[EAST][EAST] += self.config.position_process_noise.powi(2) * dt;
+        covariance[NORTH][NORTH] += self.config.position_process_noise.powi(2) * dt;
+        covariance[HEADING][HEADING] += self.config.heading_process_noise.powi(2) * dt;
+        covariance[SPEED][SPEED] += self.config.speed_process_noise.powi(2) * dt;
+
+        self.covariance = covariance;
+    }
+
+    // Measurements each observe one state directly, so they are applied one
+    // at a time as scalar updates
+    fn correct(&mut self, index: usize, residual: f64, variance: f64) {
+        let innovation = self.covariance[index][index] + variance;
+
+        if innovation <= 0.0 {
+            return;
+        }
+
+        let gain = self.covariance.map(|row| row[index] / innovation);
+
+        for (state, gain) in self.state.iter_mut().zip(gain.iter()) {
+            *state += gain * residual;
+        }
+
+        let measured_row = self.covariance[index];
+        for (row, gain) in self.covariance.iter_mut().zip(gain.iter()) {
+            for (value, measured) in row.iter_mut().zip(measured_row.iter()) {
+                *value -= gain * measured;
+            }
+        }
+    }
+}
+
+fn wrap_angle(angle: f64) -> f64 {
+    return (angle + PI).rem_euclid(TAU) - PI;
+}
+
+fn identity() -> Matrix {
+    let mut matrix = [[0.0; 4]; 4];
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+    return matrix;
+}
+
+fn transpose(matrix: &Matrix) -> Matrix {
+    let mut result = [[0.0; 4]; 4];
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, value) in row.iter().enumerate() {
+            result[j][i] = *value;
+        }
+    }
+    return result;
+}
+
+fn multiply(left: &Matrix, right: &Matrix) -> Matrix {
+    let mut result = [[0.0; 4]; 4];
+    for (result_row, left_row) in result.iter_mut().zip(left.iter()) {
+        for (column, value) in result_row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| left_row[k] * right[k][column]).sum();
+        }
+    }
+    return result;
+}
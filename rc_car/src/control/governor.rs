@@ -0,0 +1,212 @@
+use common_data::commands::ping::Ping;
+use common_data::server::data::telementry::ThrottleCapReason;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::VecDeque;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct GovernorConfig {
+    pub enabled: bool,
+    // Seconds between pings to the driver
+    pub ping_interval: f32,
+    // Seconds before an unanswered ping counts as lost
+    pub ping_timeout: f32,
+    // Number of recent pings packet loss is measured over
+    pub loss_window: usize,
+    // Round trip in ms where the cap starts and where it reaches min_throttle
+    pub rtt_start: f32,
+    pub rtt_full: f32,
+    // Fraction of pings lost where the cap starts and where it is at its lowest
+    pub loss_start: f32,
+    pub loss_full: f32,
+    // Lowest throttle cap, 0.0 to 1.0
+    pub min_throttle: f32,
+    // How fast the cap lifts once the link is better, throttle per second
+    pub recovery_rate: f32,
+}
+
+impl Default for GovernorConfig {
+    fn default() -> Self {
+        return GovernorConfig {
+            enabled: true,
+            ping_interval: 0.5,
+            ping_timeout: 2.0,
+            loss_window: 20,
+            rtt_start: 150.0,
+            rtt_full: 400.0,
+            loss_start: 0.05,
+            loss_full: 0.3,
+            min_throttle: 0.3,
+            recovery_rate: 0.2,
+        };
+    }
+}
+
+// Caps the driver's throttle when the link to them is slow or dropping packets
+#[derive(Debug, Clone)]
+pub struct Governor {
+    pub config: GovernorConfig,
+    // Smoothed round trip in ms
+    pub rtt: Option<f32>,
+    pub packet_loss: f32,
+    pub throttle_cap: f32,
+    pub reason: Option<ThrottleCapReason>,
+    clock: f64,
+    // Drivers that never echo pings are not measured, or they would only
+    // ever see lost packets
+    answering: bool,
+    since_ping: f32,
+    next_sequence: u16,
+    // Sequence and send time of pings still waiting for an answer
+    outstanding: Vec<(u16, f64)>,
+    // Whether each recent ping was answered, oldest first
+    results: VecDeque<bool>,
+}
+
+impl Governor {
+    pub fn new(config: GovernorConfig) -> Self {
+        return Governor {
+            config,
+            rtt: None,
+            packet_loss: 0.0,
+            throttle_cap: 1.0,
+            reason: None,
+            clock: 0.0,
+            answering: false,
+            since_ping: 0.0,
+            next_sequence: 0,
+            outstanding: Vec::new(),
+            results: VecDeque::new(),
+        };
+    }
+
+    // Returns a ping to send to the driver when one is due. Nothing is
+    // measured while there is no driver to answer.
+    pub fn update(&mut self, connected: bool, dt: f32) -> Option<Ping> {
+        self.clock += f64::from(dt);
+
+        if !self.config.enabled || !connected {
+            // The next driver may not echo pings, start measuring afresh
+            self.answering = false;
+            self.outstanding.clear();
+            self.results.clear();
+            self.rtt = None;
+            self.packet_loss = 0.0;
+            self.since_ping = 0.0;
+            self.update_cap(dt);
+            return None;
+        }
+
+        let timeout = f64::from(self.config.ping_timeout);
+        let clock = self.clock;
+        let expired = self
+            .outstanding
+            .iter()
+            .filter(|(_, sent)| clock - sent > timeout)
+            .count();
+        self.outstanding.retain(|(_, sent)| clock - sent <= timeout);
+
+        if self.answering {
+            for _ in 0..expired {
+                self.record(false);
+            }
+        }
+
+        self.update_cap(dt);
+
+        self.since_ping += dt;
+
+        if self.since_ping < self.config.ping_interval {
+            return None;
+        }
+
+        self.since_ping = 0.0;
+
+        let ping = Ping::new(self.next_sequence);
+        self.outstanding.push((self.next_sequence, self.clock));
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+
+        return Some(ping);
+    }
+
+    pub fn handle_pong(&mut self, sequence: u16) {
+        let index = match self.outstanding.iter().position(|(s, _)| *s == sequence) {
+            Some(i) => i,
+            // Late or duplicate, it was already counted
+            None => return,
+        };
+
+        self.answering = true;
+
+        let (_, sent) = self.outstanding.remove(index);
+        let sample = ((self.clock - sent) * 1000.0) as f32;
+
+        self.rtt = match self.rtt {
+            None => Some(sample),
+            Some(rtt) => Some(rtt + (sample - rtt) * 0.125),
+        };
+
+        self.record(true);
+    }
+
+    pub fn limit_throttle(&self, throttle: f32) -> f32 {
+        return throttle.clamp(-self.throttle_cap, self.throttle_cap);
+    }
+
+    fn record(&mut self, answered: bool) {
+        self.results.push_back(answered);
+
+        while self.results.len() > self.config.loss_window.max(1) {
+            self.results.pop_front();
+        }
+
+        let lost = self.results.iter().filter(|a| !**a).count();
+        self.packet_loss = lost as f32 / self.results.len() as f32;
+    }
+
+    fn update_cap(&mut self, dt: f32) {
+        let rtt_factor = match self.rtt {
+            Some(rtt) => ramp(rtt, self.config.rtt_start, self.config.rtt_full),
+            None => 0.0,
+        };
+        let loss_factor = ramp(
+            self.packet_loss,
+            self.config.loss_start,
+            self.config.loss_full,
+        );
+
+        let factor = rtt_factor.max(loss_factor);
+        let min_throttle = self.config.min_throttle.clamp(0.0, 1.0);
+        let target = 1.0 - (1.0 - min_throttle) * factor;
+
+        if factor > 0.0 {
+            self.reason = if rtt_factor >= loss_factor {
+                Some(ThrottleCapReason::Latency)
+            } else {
+                Some(ThrottleCapReason::PacketLoss)
+            };
+        }
+
+        // Drop straight away, come back up slowly
+        if target <= self.throttle_cap {
+            self.throttle_cap = target;
+        } else {
+            self.throttle_cap = (self.throttle_cap + self.config.recovery_rate * dt).min(target);
+        }
+
+        if self.throttle_cap >= 1.0 {
+            self.reason = None;
+        }
+    }
+}
+
+// 0.0 at or below start, 1.0 at or above full
+fn ramp(value: f32, start: f32, full: f32) -> f32 {
+    if full <= start {
+        return if value >= full { 1.0 } else { 0.0 };
+    }
+
+    return ((value - start) / (full - start)).clamp(0.0, 1.0);
+}
@@ -0,0 +1,123 @@
+use crate::control::estimator::Pose;
+use crate::control::mission::{MissionConfig, MissionRunner};
+use crate::sim::vehicle::Vehicle;
+
+use common_data::server::data::mission::{Mission, Waypoint};
+use common_data::server::data::position::Position;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ReturnHomeConfig {
+    pub enabled: bool,
+    // Seconds without control packets before the car heads home
+    pub link_loss_timeout: f32,
+    // m/s
+    pub speed: f32,
+    // Latitude and longitude, the first position fix is used when unset
+    pub home: Option<[f64; 2]>,
+}
+
+impl Default for ReturnHomeConfig {
+    fn default() -> Self {
+        return ReturnHomeConfig {
+            enabled: true,
+            link_loss_timeout: 10.0,
+            speed: 1.5,
+            home: None,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReturnHomeReason {
+    LinkLoss,
+    Battery,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReturnHome {
+    pub config: ReturnHomeConfig,
+    pub home: Option<Position>,
+    pub reason: Option<ReturnHomeReason>,
+    runner: Option<MissionRunner>,
+}
+
+impl ReturnHome {
+    pub fn new(config: ReturnHomeConfig) -> Self {
+        let home = config.home.map(|h| Position::new(h[0], h[1], 0.0));
+
+        return ReturnHome {
+            config,
+            home,
+            reason: None,
+            runner: None,
+        };
+    }
+
+    pub fn set_home_if_unset(&mut self, position: Position) {
+        if self.home.is_none() {
+            self.home = Some(position);
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        match &self.runner {
+            Some(r) => r.is_running(),
+            None => false,
+        }
+    }
+
+    // Returns false when there is no home to go to
+    pub fn start(
+        &mut self,
+        reason: ReturnHomeReason,
+        mission_config: &MissionConfig,
+        origin: &Position,
+        start: [f64; 2],
+    ) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+
+        let home = match &self.home {
+            Some(h) => h,
+            None => return false,
+        };
+
+        let mission = Mission {
+            uuid: "return-home".to_string(),
+            waypoints: vec![Waypoint {
+                latitude: home.latitude,
+                longitude: home.longitude,
+                speed: Some(self.config.speed),
+            }],
+            laps: 1,
+        };
+
+        self.runner = Some(MissionRunner::new(
+            mission_config.clone(),
+            mission,
+            origin,
+            start,
+        ));
+        self.reason = Some(reason);
+
+        return true;
+    }
+
+    pub fn cancel(&mut self) {
+        if let Some(runner) = self.runner.as_mut() {
+            runner.abort();
+        }
+    }
+
+    // Returns throttle and steering, both -1.0 to 1.0
+    pub fn update(&mut self, pose: &Pose, vehicle: &Vehicle) -> (f32, f32) {
+        match self.runner.as_mut() {
+            Some(r) => r.update(pose, vehicle),
+            None => (0.0, 0.0),
+        }
+    }
+}
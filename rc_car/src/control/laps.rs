@@ -0,0 +1,128 @@
+use crate::sim::world::World;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LapConfig {
+    pub enabled: bool,
+    // Seconds after crossing the line before crossing it again counts, so
+    // position noise at the line is not taken for a lap
+    pub min_lap_time: f32,
+}
+
+impl Default for LapConfig {
+    fn default() -> Self {
+        return LapConfig {
+            enabled: true,
+            min_lap_time: 3.0,
+        };
+    }
+}
+
+// Times laps of a closed track. The line across the first points of its two
+// sides is the start and finish, crossed going towards the second points.
+#[derive(Debug, Clone)]
+pub struct LapTimer {
+    pub config: LapConfig,
+    pub laps: u16,
+    // Seconds into the current lap, None until the car first crosses the line
+    pub lap_time: Option<f64>,
+    pub last_lap: Option<f64>,
+    pub best_lap: Option<f64>,
+    // Ends of the line and the way across it that counts
+    line: Option<([f64; 2], [f64; 2], [f64; 2])>,
+    last_position: Option<[f64; 2]>,
+}
+
+impl LapTimer {
+    pub fn new(config: LapConfig, world: &World) -> Self {
+        let line = match world.track.as_ref() {
+            Some(t) if t.closed && t.left.len() >= 2 && t.right.len() >= 2 => {
+                let (left, right) = (t.left[0], t.right[0]);
+                let along = [right[0] - left[0], right[1] - left[1]];
+                let ahead = [
+                    (t.left[1][0] + t.right[1][0] - left[0] - right[0]) / 2.0,
+                    (t.left[1][1] + t.right[1][1] - left[1] - right[1]) / 2.0,
+                ];
+
+                let mut forward = [along[1], -along[0]];
+                if forward[0] * ahead[0] + forward[1] * ahead[1] < 0.0 {
+                    forward = [-forward[0], -forward[1]];
+                }
+
+                Some((left, right, forward))
+            }
+            _ => None,
+        };
+
+        return LapTimer {
+            config,
+            laps: 0,
+            lap_time: None,
+            last_lap: None,
+            best_lap: None,
+            line,
+            last_position: None,
+        };
+    }
+
+    // Position is meters east and north, None while the car does not know
+    // where it is
+    pub fn update(&mut self, position: Option<[f64; 2]>, dt: f32) {
+        if let Some(time) = self.lap_time.as_mut() {
+            *time += f64::from(dt);
+        }
+
+        let (left, right, forward) = match self.line {
+            Some(l) if self.config.enabled => l,
+            _ => return,
+        };
+
+        let position = match position {
+            Some(p) => p,
+            None => {
+                self.last_position = None;
+                return;
+            }
+        };
+
+        let last_position = match self.last_position.replace(position) {
+            Some(p) => p,
+            None => return,
+        };
+
+        let moved = [
+            position[0] - last_position[0],
+            position[1] - last_position[1],
+        ];
+
+        if moved[0] * forward[0] + moved[1] * forward[1] <= 0.0
+            || !crosses(last_position, position, left, right)
+        {
+            return;
+        }
+
+        match self.lap_time {
+            None => self.lap_time = Some(0.0),
+            Some(t) if t >= f64::from(self.config.min_lap_time) => {
+                self.laps = self.laps.saturating_add(1);
+                self.last_lap = Some(t);
+                if self.best_lap.is_none_or(|b| t < b) {
+                    self.best_lap = Some(t);
+                }
+                self.lap_time = Some(0.0);
+            }
+            Some(_) => (),
+        };
+    }
+}
+
+// Whether the segment from a to b crosses the one from c to d
+fn crosses(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> bool {
+    let side = |p: [f64; 2], q: [f64; 2], r: [f64; 2]| -> f64 {
+        (q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0])
+    };
+
+    return side(a, b, c) * side(a, b, d) <= 0.0 && side(c, d, a) * side(c, d, b) <= 0.0;
+}
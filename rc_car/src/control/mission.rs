@@ -0,0 +1,181 @@
+use crate::control::estimator::Pose;
+use crate::control::pursuit;
+use crate::sim::vehicle::Vehicle;
+
+use common_data::server::data::mission::{Mission, MissionProgress, MissionState};
+use common_data::server::data::position::Position;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MissionConfig {
+    // Lookahead is lookahead_min + lookahead_time * speed meters
+    pub lookahead_min: f32,
+    pub lookahead_time: f32,
+    // m/s for waypoints without a speed
+    pub cruise_speed: f32,
+    // Slows the car for tight corners, m/s^2
+    pub max_lateral_acceleration: f32,
+    // Deceleration used to stop at the last waypoint, m/s^2
+    pub braking: f32,
+    // Meters from a waypoint to count it as reached
+    pub acceptance_radius: f32,
+}
+
+impl Default for MissionConfig {
+    fn default() -> Self {
+        return MissionConfig {
+            lookahead_min: 1.0,
+            lookahead_time: 0.5,
+            cruise_speed: 3.0,
+            max_lateral_acceleration: 3.0,
+            braking: 2.0,
+            acceptance_radius: 1.5,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MissionRunner {
+    pub config: MissionConfig,
+    pub mission: Mission,
+    pub state: MissionState,
+    // The start position followed by every waypoint of every lap
+    path: Vec<[f64; 2]>,
+    speeds: Vec<Option<f32>>,
+    // Distance left to drive from each path point to the end
+    remaining: Vec<f64>,
+    // Index into path of the point being driven to
+    target: usize,
+    distance_to_waypoint: f32,
+}
+
+impl MissionRunner {
+    pub fn new(
+        config: MissionConfig,
+        mission: Mission,
+        origin: &Position,
+        start: [f64; 2],
+    ) -> Self {
+        let mut path = vec![start];
+        let mut speeds = vec![None];
+
+        for _ in 0..mission.laps {
+            for waypoint in mission.waypoints.iter() {
+                let position = Position::new(waypoint.latitude, waypoint.longitude, 0.0);
+                let enu = position.to_enu(origin);
+
+                path.push([enu[0], enu[1]]);
+                speeds.push(waypoint.speed);
+            }
+        }
+
+        let mut remaining = vec![0.0; path.len()];
+        for index in (0..path.len().saturating_sub(1)).rev() {
+            remaining[index] =
+                remaining[index + 1] + pursuit::distance(path[index], path[index + 1]);
+        }
+
+        let state = if path.len() > 1 {
+            MissionState::Running
+        } else {
+            MissionState::Complete
+        };
+
+        return MissionRunner {
+            config,
+            mission,
+            state,
+            path,
+            speeds,
+            remaining,
+            target: 1,
+            distance_to_waypoint: 0.0,
+        };
+    }
+
+    pub fn is_running(&self) -> bool {
+        return self.state == MissionState::Running;
+    }
+
+    pub fn abort(&mut self) {
+        if self.is_running() {
+            self.state = MissionState::Aborted;
+        }
+    }
+
+    // Returns throttle and steering, both -1.0 to 1.0
+    pub fn update(&mut self, pose: &Pose, vehicle: &Vehicle) -> (f32, f32) {
+        if !self.is_running() {
+            return (0.0, 0.0);
+        }
+
+        let position = [pose.east, pose.north];
+
+        while self.target < self.path.len()
+            && pursuit::distance(position, self.path[self.target])
+                < f64::from(self.config.acceptance_radius)
+        {
+            self.target += 1;
+        }
+
+        if self.target >= self.path.len() {
+            self.state = MissionState::Complete;
+            self.distance_to_waypoint = 0.0;
+            return (0.0, 0.0);
+        }
+
+        let to_waypoint = pursuit::distance(position, self.path[self.target]);
+        self.distance_to_waypoint = to_waypoint as f32;
+
+        let lookahead = f64::from(
+            self.config.lookahead_min + self.config.lookahead_time * pose.speed.max(0.0) as f32,
+        );
+        let goal = pursuit::lookahead_point(&self.path, self.target - 1, position, lookahead);
+        let curvature = pursuit::curvature(pose, goal);
+
+        let steering = vehicle
+            .model
+            .steering_for_curvature(curvature as f32, pose.speed as f32);
+
+        // Speed profile: the waypoint speed, eased for corners and for stopping
+        // at the end of the route
+        let mut speed = self.speeds[self.target].unwrap_or(self.config.cruise_speed);
+
+        if curvature.abs() > 1e-3 {
+            let corner_speed =
+                (f64::from(self.config.max_lateral_acceleration) / curvature.abs()).sqrt();
+            speed = speed.min(corner_speed as f32);
+        }
+
+        let distance_left = to_waypoint + self.remaining[self.target];
+        let stopping_speed = (2.0 * f64::from(self.config.braking) * distance_left).sqrt();
+        speed = speed.min(stopping_speed as f32);
+
+        let throttle = if vehicle.config.max_speed > 0.0 {
+            (speed / vehicle.config.max_speed).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        return (throttle, steering);
+    }
+
+    pub fn progress(&self) -> MissionProgress {
+        let waypoint_count = self.mission.waypoints.len().max(1);
+        let driven = self
+            .target
+            .saturating_sub(1)
+            .min(self.path.len().saturating_sub(2));
+
+        return MissionProgress {
+            uuid: self.mission.uuid.clone(),
+            state: self.state,
+            waypoint: (driven % waypoint_count) as u32,
+            waypoint_count: self.mission.waypoints.len() as u32,
+            lap: (driven / waypoint_count) as u32,
+            distance_to_waypoint: self.distance_to_waypoint,
+        };
+    }
+}
@@ -0,0 +1,10 @@
+pub mod aeb;
+pub mod battery;
+pub mod concealment;
+pub mod cruise;
+pub mod estimator;
+pub mod governor;
+pub mod home;
+pub mod laps;
+pub mod mission;
+pub mod pursuit;
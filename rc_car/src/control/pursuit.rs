@@ -0,0 +1,75 @@
+use crate::control::estimator::Pose;
+
+// Pure pursuit steering. Points are meters east and north of the sim origin.
+
+// Curvature in 1/m of the arc from pose through target, positive to the right
+pub fn curvature(pose: &Pose, target: [f64; 2]) -> f64 {
+    let east = target[0] - pose.east;
+    let north = target[1] - pose.north;
+
+    // Target in the car frame
+    let forward = east * pose.heading.sin() + north * pose.heading.cos();
+    let right = east * pose.heading.cos() - north * pose.heading.sin();
+
+    let distance_squared = forward.powi(2) + right.powi(2);
+
+    if distance_squared < 1e-6 {
+        return 0.0;
+    }
+
+    return 2.0 * right / distance_squared;
+}
+
+// Point lookahead meters along the path past where position projects onto the
+// segment from path[segment] to path[segment + 1]
+pub fn lookahead_point(
+    path: &[[f64; 2]],
+    segment: usize,
+    position: [f64; 2],
+    lookahead: f64,
+) -> [f64; 2] {
+    if path.is_empty() {
+        return position;
+    }
+
+    if segment + 1 >= path.len() {
+        return path[path.len() - 1];
+    }
+
+    let start = path[segment];
+    let end = path[segment + 1];
+    let length = distance(start, end);
+
+    let along = if length > 0.0 {
+        let projected = ((position[0] - start[0]) * (end[0] - start[0])
+            + (position[1] - start[1]) * (end[1] - start[1]))
+            / length;
+        projected.clamp(0.0, length)
+    } else {
+        0.0
+    };
+
+    let mut remaining = lookahead + along;
+
+    for index in segment..path.len() - 1 {
+        let from = path[index];
+        let to = path[index + 1];
+        let length = distance(from, to);
+
+        if remaining <= length && length > 0.0 {
+            let ratio = remaining / length;
+            return [
+                from[0] + (to[0] - from[0]) * ratio,
+                from[1] + (to[1] - from[1]) * ratio,
+            ];
+        }
+
+        remaining -= length;
+    }
+
+    return path[path.len() - 1];
+}
+
+pub fn distance(from: [f64; 2], to: [f64; 2]) -> f64 {
+    return ((to[0] - from[0]).powi(2) + (to[1] - from[1]).powi(2)).sqrt();
+}
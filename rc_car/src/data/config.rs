@@ -0,0 +1,97 @@
+use crate::control::aeb::AebConfig;
+use crate::control::battery::BatteryLimitConfig;
+use crate::control::concealment::ConcealmentConfig;
+use crate::control::cruise::CruiseConfig;
+use crate::control::estimator::EstimatorConfig;
+use crate::control::governor::GovernorConfig;
+use crate::control::home::ReturnHomeConfig;
+use crate::control::laps::LapConfig;
+use crate::control::mission::MissionConfig;
+use crate::data::loader::{self, LoadError};
+use crate::sim::SimConfig;
+
+use common_data::server::data::capabilities::CameraGimbal;
+
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ServerConfig {
+    pub address: String,
+    pub car_id: String,
+    pub api_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CarConfig {
+    // UDP address movement packets are received on
+    pub control_address: String,
+    pub server: Option<ServerConfig>,
+    // Reported to the server, the sim has no camera to move
+    pub camera_gimbal: Option<CameraGimbal>,
+    // Every tick steps the sim by exactly 1 / tick_rate seconds
+    pub tick_rate: u32,
+    // Milliseconds since the epoch telementry times count from, set for runs
+    // that need to repeat exactly. Unset uses the time the car started.
+    pub start_time: Option<i64>,
+    // Seconds without control packets before the car stops
+    pub command_timeout: f32,
+    // Motion packets a second streamed to the driver for rig seats, 0 for none
+    pub motion_rate: f32,
+    // Status packets a second streamed to the driver for rig dashboards, 0
+    // for none
+    pub status_rate: f32,
+    pub concealment: ConcealmentConfig,
+    // Seconds between telementry reports to the server
+    pub report_interval: u64,
+    pub sim: SimConfig,
+    pub aeb: AebConfig,
+    pub battery_limits: BatteryLimitConfig,
+    pub cruise: CruiseConfig,
+    pub estimator: EstimatorConfig,
+    pub governor: GovernorConfig,
+    pub laps: LapConfig,
+    pub mission: MissionConfig,
+    pub return_home: ReturnHomeConfig,
+}
+
+impl Default for CarConfig {
+    fn default() -> Self {
+        return CarConfig {
+            control_address: "0.0.0.0:5000".to_string(),
+            server: None,
+            camera_gimbal: None,
+            tick_rate: 50,
+            start_time: None,
+            command_timeout: 0.5,
+            motion_rate: 50.0,
+            status_rate: 5.0,
+            concealment: ConcealmentConfig::default(),
+            report_interval: 5,
+            sim: SimConfig::default(),
+            aeb: AebConfig::default(),
+            battery_limits: BatteryLimitConfig::default(),
+            cruise: CruiseConfig::default(),
+            estimator: EstimatorConfig::default(),
+            governor: GovernorConfig::default(),
+            laps: LapConfig::default(),
+            mission: MissionConfig::default(),
+            return_home: ReturnHomeConfig::default(),
+        };
+    }
+}
+
+impl CarConfig {
+    pub fn load(path: &str) -> Result<CarConfig, LoadError> {
+        let path = Path::new(path);
+        let mut config: CarConfig = loader::load_json(path)?;
+
+        if let Some(world_file) = config.sim.world_file.as_ref() {
+            config.sim.world = loader::load_world(path, world_file)?;
+        }
+
+        return Ok(config);
+    }
+}
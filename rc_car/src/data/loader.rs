@@ -0,0 +1,44 @@
+use crate::sim::world::World;
+
+use serde::de::DeserializeOwned;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Why a car config, scenario, fleet or gym file could not be loaded
+#[derive(Debug, Clone)]
+pub enum LoadError {
+    ReadError,
+    DecodeError,
+    WorldNotLoaded,
+    // A car config file named by a fleet
+    CarNotLoaded,
+}
+
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Result<T, LoadError> {
+    let file = match fs::read_to_string(path) {
+        Ok(f) => f,
+        Err(_) => return Err(LoadError::ReadError),
+    };
+
+    match serde_json::from_str(&file) {
+        Ok(t) => Ok(t),
+        Err(_) => Err(LoadError::DecodeError),
+    }
+}
+
+// Files named inside another file are relative to the directory it is in
+pub fn relative(path: &Path, file: &str) -> PathBuf {
+    match path.parent() {
+        Some(p) => p.join(file),
+        None => Path::new(file).to_path_buf(),
+    }
+}
+
+// Loads a world file named inside the file at path
+pub fn load_world(path: &Path, world_file: &str) -> Result<World, LoadError> {
+    match World::load(&relative(path, world_file).to_string_lossy()) {
+        Ok(w) => Ok(w),
+        Err(_) => Err(LoadError::WorldNotLoaded),
+    }
+}
@@ -0,0 +1,72 @@
+use common_data::server::data::mission::Mission;
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+
+#[derive(Debug, Clone)]
+pub enum ScriptError {
+    ReadError,
+    DecodeError,
+}
+
+// What the driver does at a point in a script
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum ScriptAction {
+    // Stream this movement command until the next Drive or Silence
+    Drive { accelerate: i8, turn: i8 },
+    // Stop sending packets, like the link dropping
+    Silence,
+    EStop { engaged: bool },
+    Mission { mission: Mission },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScriptStep {
+    // Seconds from the start of the run
+    pub time: f64,
+    pub action: ScriptAction,
+}
+
+// Driver input for a headless run. Times are rounded to the nearest tick.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Script {
+    // Seconds to run for
+    pub duration: f64,
+    // Seconds between movement packets while driving
+    pub packet_interval: f64,
+    // Seconds between telementry samples in the output
+    pub report_interval: f64,
+    // Echo the car's pings back like a real driver, so the latency governor
+    // has something to measure
+    pub answer_pings: bool,
+    pub steps: Vec<ScriptStep>,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        return Script {
+            duration: 10.0,
+            packet_interval: 0.05,
+            report_interval: 0.5,
+            answer_pings: false,
+            steps: Vec::new(),
+        };
+    }
+}
+
+impl Script {
+    pub fn load(path: &str) -> Result<Script, ScriptError> {
+        let file = match fs::read_to_string(path) {
+            Ok(f) => f,
+            Err(_) => return Err(ScriptError::ReadError),
+        };
+
+        match serde_json::from_str(&file) {
+            Ok(s) => Ok(s),
+            Err(_) => Err(ScriptError::DecodeError),
+        }
+    }
+}
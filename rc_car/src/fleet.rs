@@ -0,0 +1,143 @@
+use crate::agent::Agent;
+use crate::data::config::CarConfig;
+use crate::data::loader::{self, LoadError};
+use crate::sim::collision;
+use crate::sim::world::{Obstacle, StartPosition, World};
+
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FleetCar {
+    // Relative to the fleet file, replaces config when set
+    #[serde(default)]
+    pub config_file: Option<String>,
+    #[serde(default)]
+    pub config: CarConfig,
+    // Replaces the car's place on the world's grid
+    #[serde(default)]
+    pub start: Option<StartPosition>,
+}
+
+// Several cars in one world, each with its own control address and server
+// login from its config
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FleetConfig {
+    // Replaces the tick rate and report interval of every car, they all step
+    // together
+    pub tick_rate: u32,
+    pub report_interval: u64,
+    // Relative to the fleet file, replaces world when set
+    pub world_file: Option<String>,
+    pub world: World,
+    pub cars: Vec<FleetCar>,
+}
+
+impl Default for FleetConfig {
+    fn default() -> Self {
+        return FleetConfig {
+            tick_rate: 50,
+            report_interval: 5,
+            world_file: None,
+            world: World::default(),
+            cars: Vec::new(),
+        };
+    }
+}
+
+impl FleetConfig {
+    pub fn load(path: &Path) -> Result<FleetConfig, LoadError> {
+        let mut fleet: FleetConfig = loader::load_json(path)?;
+
+        if let Some(world_file) = fleet.world_file.as_ref() {
+            fleet.world = loader::load_world(path, world_file)?;
+        }
+
+        for car in fleet.cars.iter_mut() {
+            if let Some(config_file) = car.config_file.as_ref() {
+                let config_path = loader::relative(path, config_file);
+
+                car.config = match CarConfig::load(&config_path.to_string_lossy()) {
+                    Ok(c) => c,
+                    Err(_) => return Err(LoadError::CarNotLoaded),
+                };
+            }
+        }
+
+        return Ok(fleet);
+    }
+
+    // Each car's config with the shared world and its own start position
+    pub fn car_configs(&self) -> Vec<CarConfig> {
+        let mut configs = Vec::new();
+
+        for (index, car) in self.cars.iter().enumerate() {
+            let mut config = car.config.clone();
+            config.tick_rate = self.tick_rate;
+            config.report_interval = self.report_interval;
+
+            let mut world = self.world.clone();
+            world.start = match (car.start.as_ref(), self.world.grid.get(index)) {
+                (Some(s), _) => s.clone(),
+                (None, Some(s)) => s.clone(),
+                (None, None) => self.world.start.clone(),
+            };
+
+            config.sim.world = world;
+            configs.push(config);
+        }
+
+        return configs;
+    }
+}
+
+// Steps every car together so they see and hit each other
+pub struct Fleet {
+    pub agents: Vec<Agent>,
+}
+
+impl Fleet {
+    pub fn new(configs: Vec<CarConfig>) -> Self {
+        return Fleet {
+            agents: configs.into_iter().map(Agent::new).collect(),
+        };
+    }
+
+    pub fn tick(&mut self, dt: f32) {
+        for index in 0..self.agents.len() {
+            self.agents[index].sim.traffic = self.traffic(index);
+        }
+
+        for agent in self.agents.iter_mut() {
+            agent.tick(dt);
+        }
+
+        for second in 1..self.agents.len() {
+            let (before, after) = self.agents.split_at_mut(second);
+
+            for first in before.iter_mut() {
+                collision::collide_vehicles(&mut first.sim.vehicle, &mut after[0].sim.vehicle);
+            }
+        }
+    }
+
+    // Every other car the one at index could see or hit
+    fn traffic(&self, index: usize) -> Vec<Obstacle> {
+        let own = &self.agents[index].sim.vehicle.state;
+
+        return self
+            .agents
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != index)
+            .map(|(_, a)| &a.sim.vehicle)
+            .filter(|v| collision::can_meet(own, &v.state))
+            .map(|v| Obstacle::Circle {
+                center: [v.state.x, v.state.y],
+                radius: f64::from(v.config.radius),
+            })
+            .collect();
+    }
+}
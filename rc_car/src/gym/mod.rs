@@ -0,0 +1,267 @@
+pub mod reward;
+pub mod server;
+
+use crate::agent::Agent;
+use crate::data::config::CarConfig;
+use crate::data::loader::{self, LoadError};
+
+use reward::{Reward, RewardConfig};
+
+use common_data::commands::movement::Movement;
+use common_data::commands::ping;
+use common_data::server::data::telementry::Telementry;
+
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+// Training environment served to learning code over a local socket
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct GymConfig {
+    // TCP address learning code connects to, each connection gets its own
+    // environment
+    pub address: String,
+    pub config: CarConfig,
+    // Relative to the gym file, replaces the world in config
+    pub world_file: Option<String>,
+    // Control ticks each step holds the action for
+    pub action_repeat: u32,
+    // Seconds before an episode is cut short
+    pub max_time: f64,
+    pub reward: RewardConfig,
+}
+
+impl Default for GymConfig {
+    fn default() -> Self {
+        return GymConfig {
+            address: "127.0.0.1:5600".to_string(),
+            config: CarConfig {
+                start_time: Some(0),
+                ..CarConfig::default()
+            },
+            world_file: None,
+            action_repeat: 5,
+            max_time: 60.0,
+            reward: RewardConfig::default(),
+        };
+    }
+}
+
+impl GymConfig {
+    pub fn load(path: &Path) -> Result<GymConfig, LoadError> {
+        let mut gym: GymConfig = loader::load_json(path)?;
+
+        if let Some(world_file) = gym.world_file.as_ref() {
+            gym.config.sim.world = loader::load_world(path, world_file)?;
+        }
+
+        // Episodes have to replay exactly, the clock must not leak in
+        if gym.config.start_time.is_none() {
+            gym.config.start_time = Some(0);
+        }
+
+        return Ok(gym);
+    }
+}
+
+// The control axes, both -1.0 to 1.0. Sent to the car as a driver's movement
+// packet would be.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct Action {
+    pub accelerate: f32,
+    pub turn: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Observation {
+    // Seconds into the episode
+    pub time: f64,
+    // What the car would report to the server
+    pub telementry: Telementry,
+    // Meters for each range sensor in config order, None when nothing is
+    // within range
+    pub ranges: Vec<Option<f32>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StepResult {
+    pub observation: Observation,
+    pub reward: f32,
+    // The reward ended the episode
+    pub terminated: bool,
+    // The episode ran out of time
+    pub truncated: bool,
+}
+
+// What learning code needs to size its spaces
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Description {
+    // Seconds each step covers
+    pub step_time: f64,
+    pub max_time: f64,
+    // Degrees clockwise from straight ahead and meters, for each range sensor
+    pub range_angles: Vec<f32>,
+    pub max_ranges: Vec<f32>,
+}
+
+// One car on the deterministic sim, stepped by learning code instead of real
+// time. Resetting with the same seed replays the same episode for the same
+// actions.
+pub struct Environment {
+    pub config: GymConfig,
+    pub agent: Agent,
+    pub reward: Box<dyn Reward>,
+    pub episode_reward: f64,
+    dt: f32,
+}
+
+impl Environment {
+    pub fn new(config: GymConfig) -> Self {
+        let reward = config.reward.build();
+
+        return Environment::with_reward(config, reward);
+    }
+
+    pub fn with_reward(config: GymConfig, reward: Box<dyn Reward>) -> Self {
+        let dt = 1.0 / config.config.tick_rate.max(1) as f32;
+
+        let mut environment = Environment {
+            agent: Agent::new(config.config.clone()),
+            config,
+            reward,
+            episode_reward: 0.0,
+            dt,
+        };
+        environment.reset(None);
+
+        return environment;
+    }
+
+    // Puts the car back on the start, seed replaces the sim's seed
+    pub fn reset(&mut self, seed: Option<u64>) -> Observation {
+        let mut config = self.config.config.clone();
+
+        if let Some(seed) = seed {
+            config.sim.seed = seed;
+        }
+
+        self.agent = Agent::new(config);
+        self.reward.reset(&self.agent);
+        self.episode_reward = 0.0;
+
+        return self.observe();
+    }
+
+    // Holds the action for action_repeat ticks
+    pub fn step(&mut self, action: Action) -> StepResult {
+        let mut movement = Movement::new();
+        let _ = movement.set_accelerate(to_percent(action.accelerate));
+        let _ = movement.set_turn(to_percent(action.turn));
+        let packet = movement.generate_packet();
+
+        let mut reward = 0.0;
+        let mut terminated = false;
+
+        for _ in 0..self.config.action_repeat.max(1) {
+            self.agent.handle_packet(&packet);
+            self.agent.tick(self.dt);
+
+            // The learning code answers pings at once, the rest of what the
+            // car streams has nowhere to go
+            for packet in self.agent.take_packets() {
+                if packet.first() == Some(&ping::COMMAND_NUMBER) {
+                    self.agent.handle_packet(&packet);
+                }
+            }
+
+            let (tick_reward, done) = self.reward.step(&self.agent, self.dt);
+            reward += tick_reward;
+
+            if done {
+                terminated = true;
+                break;
+            }
+        }
+
+        self.episode_reward += f64::from(reward);
+
+        // Half a tick of slack, the sim clock adds up in f32 steps
+        let truncated =
+            !terminated && self.agent.sim_time + f64::from(self.dt) / 2.0 >= self.config.max_time;
+
+        return StepResult {
+            observation: self.observe(),
+            reward,
+            terminated,
+            truncated,
+        };
+    }
+
+    pub fn observe(&self) -> Observation {
+        return Observation {
+            time: self.agent.sim_time,
+            telementry: self.agent.telementry(),
+            ranges: self.agent.sim.sensors.readings.ranges.clone(),
+        };
+    }
+
+    pub fn describe(&self) -> Description {
+        let ranges = &self.config.config.sim.sensors.range;
+
+        return Description {
+            step_time: f64::from(self.dt) * f64::from(self.config.action_repeat.max(1)),
+            max_time: self.config.max_time,
+            range_angles: ranges.iter().map(|r| r.angle).collect(),
+            max_ranges: ranges.iter().map(|r| r.max_range).collect(),
+        };
+    }
+}
+
+fn to_percent(axis: f32) -> i8 {
+    if axis.is_nan() {
+        return 0;
+    }
+
+    return (axis.clamp(-1.0, 1.0) * 100.0).round() as i8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn environment() -> Environment {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("gyms/oval_laps.json");
+
+        return Environment::new(GymConfig::load(&path).unwrap());
+    }
+
+    // Observations and rewards as JSON for a few steps of varied actions
+    fn episode(environment: &mut Environment, seed: u64) -> Vec<String> {
+        let mut trace = vec![serde_json::to_string(&environment.reset(Some(seed))).unwrap()];
+
+        for step in 0..30 {
+            let action = Action {
+                accelerate: 0.5,
+                turn: (step as f32 / 5.0).sin() * 0.3,
+            };
+            let result = environment.step(action);
+
+            trace.push(serde_json::to_string(&result).unwrap());
+        }
+
+        return trace;
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_episode() {
+        let mut first = environment();
+        let mut second = environment();
+
+        let replay = episode(&mut first, 3);
+        assert_eq!(replay, episode(&mut second, 3));
+        // Resetting the same environment replays it too
+        assert_eq!(replay, episode(&mut first, 3));
+        assert_ne!(replay, episode(&mut second, 4));
+    }
+}
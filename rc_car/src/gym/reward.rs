@@ -0,0 +1,227 @@
+use crate::agent::Agent;
+use crate::control::pursuit::distance;
+use crate::sim::world::{nearest_on_segment, World};
+
+use serde::{Deserialize, Serialize};
+
+// Clearance under which the car counts as touching what it hit. Collisions
+// push it back out to exactly its radius.
+const CONTACT_MARGIN: f64 = 0.01;
+
+// Scores what the car did on each tick of an episode. Training code can use
+// its own by handing one to Environment::with_reward.
+pub trait Reward: Send {
+    // Called at the start of each episode with the car on the start
+    fn reset(&mut self, agent: &Agent);
+
+    // Reward for the tick just run, and whether it ended the episode
+    fn step(&mut self, agent: &Agent, dt: f32) -> (f32, bool);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum RewardConfig {
+    TrackProgress(TrackProgressConfig),
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        return RewardConfig::TrackProgress(TrackProgressConfig::default());
+    }
+}
+
+impl RewardConfig {
+    pub fn build(&self) -> Box<dyn Reward> {
+        match self {
+            RewardConfig::TrackProgress(c) => Box::new(TrackProgress::new(c.clone())),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TrackProgressConfig {
+    // Reward per meter along the middle of the track, going backwards costs
+    // the same
+    pub progress_gain: f32,
+    // Taken once for each tick the car is against a wall or track edge
+    pub collision_penalty: f32,
+    pub end_on_collision: bool,
+    // Taken every second so dawdling costs
+    pub time_penalty: f32,
+}
+
+impl Default for TrackProgressConfig {
+    fn default() -> Self {
+        return TrackProgressConfig {
+            progress_gain: 1.0,
+            collision_penalty: 10.0,
+            end_on_collision: true,
+            time_penalty: 0.0,
+        };
+    }
+}
+
+// Rewards distance made good along the track. The middle of the track is
+// the line through the midpoints of each pair of points on its sides. An
+// open track ends the episode at its far end, a world without a track only
+// gives the penalties.
+#[derive(Debug, Clone)]
+pub struct TrackProgress {
+    pub config: TrackProgressConfig,
+    // Meters made good along the track this episode
+    pub progress: f64,
+    middle: Vec<[f64; 2]>,
+    // Meters along the middle to each of its points
+    along: Vec<f64>,
+    closed: bool,
+    length: f64,
+    last_along: Option<f64>,
+}
+
+impl TrackProgress {
+    pub fn new(config: TrackProgressConfig) -> Self {
+        return TrackProgress {
+            config,
+            progress: 0.0,
+            middle: Vec::new(),
+            along: Vec::new(),
+            closed: false,
+            length: 0.0,
+            last_along: None,
+        };
+    }
+
+    fn set_track(&mut self, world: &World) {
+        self.middle = match world.track.as_ref() {
+            Some(t) => t
+                .left
+                .iter()
+                .zip(t.right.iter())
+                .map(|(l, r)| [(l[0] + r[0]) / 2.0, (l[1] + r[1]) / 2.0])
+                .collect(),
+            None => Vec::new(),
+        };
+        self.closed = world.track.as_ref().is_some_and(|t| t.closed) && self.middle.len() > 2;
+
+        if self.closed {
+            self.middle.push(self.middle[0]);
+        }
+
+        self.along = vec![0.0];
+        for pair in self.middle.windows(2) {
+            let last = self.along[self.along.len() - 1];
+            self.along.push(last + distance(pair[0], pair[1]));
+        }
+
+        self.length = self.along[self.along.len() - 1];
+    }
+
+    // Meters along the middle of the track to the nearest point on it
+    fn along_track(&self, point: [f64; 2]) -> Option<f64> {
+        let mut nearest: Option<(f64, f64)> = None;
+
+        for (index, pair) in self.middle.windows(2).enumerate() {
+            let on_track = nearest_on_segment(point, pair[0], pair[1]);
+            let away = distance(point, on_track);
+
+            if nearest.is_none_or(|(d, _)| away < d) {
+                nearest = Some((away, self.along[index] + distance(pair[0], on_track)));
+            }
+        }
+
+        return nearest.map(|(_, along)| along);
+    }
+}
+
+impl Reward for TrackProgress {
+    fn reset(&mut self, agent: &Agent) {
+        self.set_track(&agent.sim.config.world);
+
+        let state = &agent.sim.vehicle.state;
+        self.progress = 0.0;
+        self.last_along = self.along_track([state.x, state.y]);
+    }
+
+    fn step(&mut self, agent: &Agent, dt: f32) -> (f32, bool) {
+        let vehicle = &agent.sim.vehicle;
+        let position = [vehicle.state.x, vehicle.state.y];
+
+        let mut reward = -self.config.time_penalty * dt;
+        let mut done = false;
+
+        if let (Some(along), Some(last_along)) = (self.along_track(position), self.last_along) {
+            let mut moved = along - last_along;
+
+            // Crossing the start of a closed track
+            if self.closed {
+                if moved > self.length / 2.0 {
+                    moved -= self.length;
+                } else if moved < -self.length / 2.0 {
+                    moved += self.length;
+                }
+            }
+
+            self.progress += moved;
+            self.last_along = Some(along);
+            reward += moved as f32 * self.config.progress_gain;
+
+            if !self.closed && along >= self.length {
+                done = true;
+            }
+        }
+
+        let touching = agent
+            .sim
+            .config
+            .world
+            .clearance(position)
+            .is_some_and(|c| c <= f64::from(vehicle.config.radius) + CONTACT_MARGIN);
+
+        if touching {
+            reward -= self.config.collision_penalty;
+            done = done || self.config.end_on_collision;
+        }
+
+        return (reward, done);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gym::{Action, Environment, GymConfig};
+
+    use std::path::Path;
+
+    #[test]
+    fn progress_along_the_track_is_rewarded() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("gyms/oval_laps.json");
+        let config = GymConfig::load(&path).unwrap();
+        let mut environment = Environment::new(config);
+
+        let action = Action {
+            accelerate: 0.4,
+            turn: 0.0,
+        };
+
+        let mut last = environment.episode_reward;
+        for _ in 0..20 {
+            let result = environment.step(action);
+
+            assert!(!result.terminated);
+            assert!(result.reward >= 0.0);
+            last = environment.episode_reward;
+        }
+        assert!(last > 1.0, "{}", last);
+
+        // Backing up gives it back
+        let reverse = Action {
+            accelerate: -0.4,
+            turn: 0.0,
+        };
+        for _ in 0..40 {
+            environment.step(reverse);
+        }
+        assert!(environment.episode_reward < last);
+    }
+}
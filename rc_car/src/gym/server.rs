@@ -0,0 +1,86 @@
+use crate::gym::{Action, Description, Environment, GymConfig, Observation, StepResult};
+
+use serde::{Deserialize, Serialize};
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+// Requests and responses are one JSON object a line, eg.
+//
+//   {"type":"Reset","seed":3}
+//   {"type":"Step","action":{"accelerate":0.5,"turn":-0.2}}
+//
+// and each request gets one response line back.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum Request {
+    Describe,
+    Reset {
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+    Step {
+        action: Action,
+    },
+    Observe,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum Response {
+    Description(Description),
+    Observation(Observation),
+    Step(StepResult),
+    Error { message: String },
+}
+
+// Serves environments until the process is stopped, each connection gets its
+// own so episodes can run side by side
+pub async fn serve(config: GymConfig) {
+    let listener = TcpListener::bind(&config.address)
+        .await
+        .expect("cannot bind to gym address");
+
+    println!("Gym listening on {}", config.address);
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((s, _)) => s,
+            Err(_) => continue,
+        };
+
+        let config = config.clone();
+
+        tokio::spawn(async move {
+            session(stream, config).await;
+        });
+    }
+}
+
+async fn session(stream: TcpStream, config: GymConfig) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut environment = Environment::new(config);
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Describe) => Response::Description(environment.describe()),
+            Ok(Request::Reset { seed }) => Response::Observation(environment.reset(seed)),
+            Ok(Request::Step { action }) => Response::Step(environment.step(action)),
+            Ok(Request::Observe) => Response::Observation(environment.observe()),
+            Err(e) => Response::Error {
+                message: format!("cannot decode request, {}", e),
+            },
+        };
+
+        let mut encoded = match serde_json::to_string(&response) {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+        encoded.push('\n');
+
+        if writer.write_all(encoded.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
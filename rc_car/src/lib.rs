@@ -0,0 +1,8 @@
+pub mod agent;
+pub mod control;
+pub mod data;
+pub mod fleet;
+pub mod gym;
+pub mod runner;
+pub mod scenario;
+pub mod sim;
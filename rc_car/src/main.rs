@@ -1,3 +1,284 @@
-fn main() {
-    println!("Hello, world!");
+use rc_car::agent::Agent;
+use rc_car::data::config::CarConfig;
+use rc_car::data::loader::LoadError;
+use rc_car::data::script::Script;
+use rc_car::fleet::{Fleet, FleetConfig};
+use rc_car::gym::{self, GymConfig};
+use rc_car::runner;
+use rc_car::scenario::{self, Scenario};
+
+use common_data::server::data::mission::Mission;
+use common_data::server::http::CarHttp;
+
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use tokio::time::{interval, Duration, Instant};
+
+use std::env;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+
+// Most ticks run at once when the loop falls behind
+const MAX_CATCH_UP: u32 = 5;
+
+#[tokio::main]
+async fn main() {
+    // Runs a scenario file, or every scenario in a directory, and fails the
+    // process if any expectation is not met
+    if let Ok(scenario_path) = env::var("CAR_SCENARIO") {
+        process::exit(run_scenarios(Path::new(&scenario_path)));
+    }
+
+    // Several cars sharing one world, each driven over its own control address
+    if let Ok(fleet_path) = env::var("CAR_FLEET") {
+        let fleet = match FleetConfig::load(Path::new(&fleet_path)) {
+            Ok(f) => f,
+            Err(e) => panic!("cannot load fleet file, {:?}", e),
+        };
+
+        drive(fleet.car_configs(), fleet.tick_rate, fleet.report_interval).await;
+        return;
+    }
+
+    // Training environments for learning code, stepped over a local socket
+    // instead of in real time
+    if let Ok(gym_path) = env::var("CAR_GYM") {
+        let gym = match GymConfig::load(Path::new(&gym_path)) {
+            Ok(g) => g,
+            Err(e) => panic!("cannot load gym file, {:?}", e),
+        };
+
+        gym::server::serve(gym).await;
+        return;
+    }
+
+    let config_path = match env::var("CAR_CONFIG") {
+        Err(_) => "car.json".to_string(),
+        Ok(v) => v,
+    };
+
+    let config = match CarConfig::load(&config_path) {
+        Ok(c) => c,
+        Err(LoadError::WorldNotLoaded) => panic!("cannot load world file"),
+        Err(_) => {
+            println!("Warning: Car config not loaded, using defaults");
+            CarConfig::default()
+        }
+    };
+
+    // Headless run of a driver script, telementry goes to stdout as JSON lines
+    if let Ok(script_path) = env::var("CAR_SCRIPT") {
+        let script = match Script::load(&script_path) {
+            Ok(s) => s,
+            Err(_) => panic!("cannot load script"),
+        };
+
+        let mut agent = Agent::new(config);
+
+        runner::run(&mut agent, &script, |telementry| {
+            match serde_json::to_string(telementry) {
+                Ok(s) => println!("{}", s),
+                Err(_) => println!("Warning: Cannot encode telementry"),
+            };
+        });
+
+        return;
+    }
+
+    let tick_rate = config.tick_rate;
+    let report_interval = config.report_interval;
+
+    drive(vec![config], tick_rate, report_interval).await;
+}
+
+// Runs the cars in real time in one world. Each listens for its own driver
+// on its control address and reports to the server as itself.
+async fn drive(configs: Vec<CarConfig>, tick_rate: u32, report_interval: u64) {
+    let car_count = configs.len();
+
+    // Packets from every car's socket, with the index of the car they are for
+    let (packet_tx, mut packet_rx) = mpsc::channel::<(usize, Vec<u8>, SocketAddr)>(64);
+    let mut sockets: Vec<Arc<UdpSocket>> = Vec::new();
+    let mut car_https: Vec<Option<CarHttp>> = Vec::new();
+
+    for (index, config) in configs.iter().enumerate() {
+        let socket = Arc::new(
+            UdpSocket::bind(&config.control_address)
+                .await
+                .expect("cannot bind to control address"),
+        );
+
+        let receiver = socket.clone();
+        let packet_tx = packet_tx.clone();
+
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 64];
+
+            loop {
+                let (size, address) = match receiver.recv_from(&mut buffer).await {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                };
+
+                if packet_tx
+                    .send((index, buffer[..size].to_vec(), address))
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+
+        sockets.push(socket);
+        car_https.push(match config.server.clone() {
+            None => None,
+            Some(s) => Some(CarHttp::new(s.address, s.car_id, s.api_key)),
+        });
+    }
+
+    let step = Duration::from_secs_f64(1.0 / f64::from(tick_rate.max(1)));
+    let mut tick = interval(step);
+    let mut report = interval(Duration::from_secs(report_interval.max(1)));
+
+    let mut fleet = Fleet::new(configs);
+
+    for (agent, car_http) in fleet.agents.iter_mut().zip(car_https.iter()) {
+        let http = match car_http {
+            None => continue,
+            Some(h) => h,
+        };
+
+        match http.get_calibration().await {
+            Ok(c) => agent.calibration = c,
+            Err(_) => println!("Warning: Cannot fetch calibration from server, using defaults"),
+        };
+
+        if http.put_capabilities(&agent.capabilities()).await.is_err() {
+            println!("Warning: Cannot report capabilities to server");
+        }
+    }
+
+    let mut last_tick = Instant::now();
+    // Real time not yet simulated, stepped off in fixed ticks
+    let mut behind = Duration::ZERO;
+    // Where each car's control packets last came from, pings go back there
+    let mut drivers: Vec<Option<SocketAddr>> = vec![None; car_count];
+
+    // Missions are fetched off the control loop and handed back here
+    let (mission_tx, mut mission_rx) = mpsc::channel::<(usize, Mission)>(car_count.max(1));
+    let mut started_missions: Vec<Option<String>> = vec![None; car_count];
+
+    loop {
+        tokio::select! {
+            Some((index, packet, address)) = packet_rx.recv() => {
+                drivers[index] = Some(address);
+                fleet.agents[index].handle_packet(&packet);
+            }
+            Some((index, mission)) = mission_rx.recv() => {
+                if started_missions[index].as_ref() == Some(&mission.uuid) {
+                    continue;
+                }
+
+                let uuid = mission.uuid.clone();
+
+                if fleet.agents[index].start_mission(mission) {
+                    started_missions[index] = Some(uuid);
+                }
+            }
+            _ = tick.tick() => {
+                let now = Instant::now();
+                behind += now - last_tick;
+                last_tick = now;
+
+                // Catch up after a stall, but not so far the cars jump
+                behind = behind.min(step * MAX_CATCH_UP);
+
+                while behind >= step {
+                    fleet.tick(step.as_secs_f32());
+                    behind -= step;
+                }
+
+                for (index, agent) in fleet.agents.iter_mut().enumerate() {
+                    let packets = agent.take_packets();
+
+                    if let Some(address) = drivers[index] {
+                        for packet in packets.iter() {
+                            let _ = sockets[index].send_to(packet, address).await;
+                        }
+                    }
+                }
+            }
+            _ = report.tick() => {
+                for (index, agent) in fleet.agents.iter().enumerate() {
+                    let http = match car_https[index].clone() {
+                        None => continue,
+                        Some(h) => h,
+                    };
+
+                    let telementry = agent.telementry();
+                    let mission_tx = mission_tx.clone();
+
+                    tokio::spawn(async move {
+                        if http.put_telementry(&telementry).await.is_err() {
+                            println!("Warning: Cannot report telementry to server");
+                        }
+
+                        match http.get_mission().await {
+                            Ok(Some(m)) => {
+                                let _ = mission_tx.send((index, m)).await;
+                            }
+                            Ok(None) => (),
+                            Err(_) => println!("Warning: Cannot fetch mission from server"),
+                        };
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn run_scenarios(path: &Path) -> i32 {
+    let paths = scenario::scenario_paths(path);
+
+    // A mistyped path must not pass as nothing to check
+    if paths.is_empty() {
+        println!("FAIL no scenario files found at {}", path.display());
+        return 1;
+    }
+
+    let mut failed = 0;
+
+    for scenario_path in paths.iter() {
+        let scenario = match Scenario::load(scenario_path) {
+            Ok(s) => s,
+            Err(e) => {
+                println!("FAIL {}: cannot load, {:?}", scenario_path.display(), e);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let failures = scenario.run();
+
+        if failures.is_empty() {
+            println!("PASS {}", scenario.name);
+        } else {
+            failed += 1;
+
+            for failure in failures.iter() {
+                println!("FAIL {}: {}", scenario.name, failure);
+            }
+        }
+    }
+
+    println!("{} passed, {} failed", paths.len() - failed, failed);
+
+    if failed > 0 {
+        return 1;
+    }
+
+    return 0;
 }
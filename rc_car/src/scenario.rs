@@ -0,0 +1,205 @@
+use crate::agent::Agent;
+use crate::data::config::CarConfig;
+use crate::data::loader::{self, LoadError};
+use crate::data::script::Script;
+use crate::runner::Runner;
+use crate::sim::vehicle::VehicleConfig;
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Below this the car counts as stopped, m/s
+const STOPPED_SPEED: f32 = 0.1;
+
+// Checked against the true state of the sim, not what the sensors report
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum Expectation {
+    // Gets within radius meters of point, meters east and north of the
+    // origin, within this many seconds
+    ReachCheckpoint {
+        point: [f64; 2],
+        radius: f64,
+        within: f64,
+    },
+    // Never faster than this many m/s
+    MaxSpeed {
+        speed: f32,
+    },
+    // Never closer than this many meters to an obstacle or track edge
+    MinClearance {
+        distance: f64,
+    },
+    // Stopped at this many seconds in
+    StoppedAt {
+        time: f64,
+    },
+}
+
+// A world, a vehicle and a driver script, run headless and checked against
+// the expectations
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub config: CarConfig,
+    // Relative to the scenario file, replaces the world in config
+    #[serde(default)]
+    pub world_file: Option<String>,
+    // Replaces the vehicle in config
+    #[serde(default)]
+    pub vehicle: Option<VehicleConfig>,
+    pub script: Script,
+    pub expectations: Vec<Expectation>,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Scenario, LoadError> {
+        let mut scenario: Scenario = loader::load_json(path)?;
+
+        if let Some(world_file) = scenario.world_file.as_ref() {
+            scenario.config.sim.world = loader::load_world(path, world_file)?;
+        }
+
+        if let Some(vehicle) = scenario.vehicle.as_ref() {
+            scenario.config.sim.vehicle = vehicle.clone();
+        }
+
+        return Ok(scenario);
+    }
+
+    // Returns why each failed expectation failed, empty when the scenario
+    // passed
+    pub fn run(&self) -> Vec<String> {
+        let mut agent = Agent::new(self.config.clone());
+        let mut runner = Runner::new(&self.script, self.config.tick_rate);
+
+        // What each expectation has seen so far
+        let mut reached: Vec<Option<f64>> = vec![None; self.expectations.len()];
+        let mut worst: Vec<Option<f64>> = vec![None; self.expectations.len()];
+
+        while runner.step(&mut agent) {
+            let time = runner.time();
+            let vehicle = &agent.sim.vehicle;
+            let position = [vehicle.state.x, vehicle.state.y];
+
+            for (index, expectation) in self.expectations.iter().enumerate() {
+                match expectation {
+                    Expectation::ReachCheckpoint { point, radius, .. } => {
+                        let distance = ((point[0] - position[0]).powi(2)
+                            + (point[1] - position[1]).powi(2))
+                        .sqrt();
+
+                        if reached[index].is_none() && distance <= *radius {
+                            reached[index] = Some(time);
+                        }
+                    }
+                    Expectation::MaxSpeed { .. } => {
+                        let speed = f64::from(vehicle.state.speed.abs());
+
+                        if worst[index].is_none_or(|w| speed > w) {
+                            worst[index] = Some(speed);
+                        }
+                    }
+                    Expectation::MinClearance { .. } => {
+                        if let Some(clearance) = agent.sim.config.world.clearance(position) {
+                            if worst[index].is_none_or(|w| clearance < w) {
+                                worst[index] = Some(clearance);
+                            }
+                        }
+                    }
+                    Expectation::StoppedAt { time: at } => {
+                        // Sampled on the first tick at or after the time
+                        if worst[index].is_none() && time + 1e-9 >= *at {
+                            worst[index] = Some(f64::from(vehicle.state.speed.abs()));
+                        }
+                    }
+                };
+            }
+        }
+
+        let mut failures = Vec::new();
+
+        for (index, expectation) in self.expectations.iter().enumerate() {
+            match expectation {
+                Expectation::ReachCheckpoint { point, within, .. } => match reached[index] {
+                    Some(t) if t <= *within => (),
+                    Some(t) => failures.push(format!(
+                        "reached checkpoint {:?} at {:.2} s, expected within {:.2} s",
+                        point, t, within
+                    )),
+                    None => failures.push(format!("never reached checkpoint {:?}", point)),
+                },
+                Expectation::MaxSpeed { speed } => match worst[index] {
+                    Some(s) if s > f64::from(*speed) => failures.push(format!(
+                        "reached {:.2} m/s, expected at most {:.2} m/s",
+                        s, speed
+                    )),
+                    _ => (),
+                },
+                Expectation::MinClearance { distance } => match worst[index] {
+                    Some(c) if c < *distance => failures.push(format!(
+                        "came within {:.2} m of an obstacle, expected at least {:.2} m",
+                        c, distance
+                    )),
+                    _ => (),
+                },
+                Expectation::StoppedAt { time } => match worst[index] {
+                    Some(s) if s < f64::from(STOPPED_SPEED) => (),
+                    Some(s) => failures.push(format!(
+                        "moving at {:.2} m/s at {:.2} s, expected to be stopped",
+                        s, time
+                    )),
+                    None => failures.push(format!("script ended before {:.2} s", time)),
+                },
+            };
+        }
+
+        return failures;
+    }
+}
+
+// The scenario file at path, or every scenario file in it when it is a
+// directory, in name order. Empty when there are none.
+pub fn scenario_paths(path: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(path) {
+        Err(_) if path.is_file() => vec![path.to_path_buf()],
+        Err(_) => Vec::new(),
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect(),
+    };
+    paths.sort();
+
+    return paths;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_scenarios_pass() {
+        let paths = scenario_paths(&Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios"));
+        assert!(!paths.is_empty());
+
+        for path in paths.iter() {
+            let scenario = match Scenario::load(path) {
+                Ok(s) => s,
+                Err(e) => panic!("cannot load {}, {:?}", path.display(), e),
+            };
+
+            assert_eq!(scenario.run(), Vec::<String>::new(), "{}", scenario.name);
+        }
+    }
+
+    #[test]
+    fn missing_directory_has_no_scenarios() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("no_such_scenarios");
+        assert!(scenario_paths(&path).is_empty());
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BatteryConfig {
+    pub capacity_mah: f32,
+    // Draw from the receiver, ESC and camera while stopped
+    pub idle_current_ma: f32,
+    // Extra draw from the motor at full throttle
+    pub max_current_ma: f32,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        return BatteryConfig {
+            capacity_mah: 5000.0,
+            idle_current_ma: 300.0,
+            max_current_ma: 40000.0,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Battery {
+    pub config: BatteryConfig,
+    pub remaining_mah: f32,
+    pub current_ma: f32,
+    pub motor_current_ma: f32,
+}
+
+impl Battery {
+    pub fn new(config: BatteryConfig) -> Self {
+        return Battery {
+            remaining_mah: config.capacity_mah,
+            current_ma: 0.0,
+            motor_current_ma: 0.0,
+            config,
+        };
+    }
+
+    pub fn step(&mut self, throttle: f32, dt: f32) {
+        self.motor_current_ma = throttle.abs().min(1.0) * self.config.max_current_ma;
+        self.current_ma = self.config.idle_current_ma + self.motor_current_ma;
+
+        let used_mah = self.current_ma * dt / 3600.0;
+        self.remaining_mah = (self.remaining_mah - used_mah).max(0.0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.remaining_mah <= 0.0;
+    }
+
+    pub fn charge_percent(&self) -> u8 {
+        if self.config.capacity_mah <= 0.0 {
+            return 0;
+        }
+
+        let percent = self.remaining_mah / self.config.capacity_mah * 100.0;
+
+        return percent.round().clamp(0.0, 100.0) as u8;
+    }
+}
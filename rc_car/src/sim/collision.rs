@@ -0,0 +1,142 @@
+use crate::sim::vehicle::{Vehicle, VehicleState};
+use crate::sim::world::{nearest_on_segment, Obstacle, World};
+
+// Fraction of the closing speed vehicles bounce back with
+const RESTITUTION: f32 = 0.3;
+
+// Obstacles and ground vehicles are about this tall, anything flying higher
+// passes over them
+const OBSTACLE_HEIGHT: f32 = 1.0;
+
+// Vehicles closer in height than this can hit each other
+const VEHICLE_HEIGHT: f32 = 0.5;
+
+// Pushes the vehicle back out of any obstacle or track edge it has driven
+// into and bounces it off. Returns true when it hit something.
+pub fn collide_world(vehicle: &mut Vehicle, world: &World) -> bool {
+    if vehicle.state.height > OBSTACLE_HEIGHT {
+        return false;
+    }
+
+    let radius = f64::from(vehicle.config.radius);
+    let mut hit = false;
+
+    for obstacle in world.obstacles.iter() {
+        let (distance, away) = match obstacle {
+            Obstacle::Circle {
+                center,
+                radius: obstacle_radius,
+            } => {
+                let (distance, away) = offset(*center, [vehicle.state.x, vehicle.state.y]);
+                (distance - obstacle_radius, away)
+            }
+            Obstacle::Wall { from, to } => {
+                let nearest = nearest_on_segment([vehicle.state.x, vehicle.state.y], *from, *to);
+                offset(nearest, [vehicle.state.x, vehicle.state.y])
+            }
+        };
+
+        if distance < radius {
+            push_out(&mut vehicle.state, away, radius - distance);
+            hit = true;
+        }
+    }
+
+    for (from, to) in world.track_edges() {
+        let nearest = nearest_on_segment([vehicle.state.x, vehicle.state.y], from, to);
+        let (distance, away) = offset(nearest, [vehicle.state.x, vehicle.state.y]);
+
+        if distance < radius {
+            push_out(&mut vehicle.state, away, radius - distance);
+            hit = true;
+        }
+    }
+
+    return hit;
+}
+
+// Separates two vehicles that overlap and trades their closing speed as if
+// they weighed the same. Returns true when they hit.
+pub fn collide_vehicles(first: &mut Vehicle, second: &mut Vehicle) -> bool {
+    if !can_meet(&first.state, &second.state) {
+        return false;
+    }
+
+    let reach = f64::from(first.config.radius + second.config.radius);
+    let (distance, away) = offset(
+        [second.state.x, second.state.y],
+        [first.state.x, first.state.y],
+    );
+
+    if distance >= reach {
+        return false;
+    }
+
+    // Each moves half of the overlap
+    let overlap = (reach - distance) / 2.0;
+    first.state.x += away[0] * overlap;
+    first.state.y += away[1] * overlap;
+    second.state.x -= away[0] * overlap;
+    second.state.y -= away[1] * overlap;
+
+    let first_velocity = first.state.velocity();
+    let second_velocity = second.state.velocity();
+    let normal = [away[0] as f32, away[1] as f32];
+
+    // Only bodies moving towards each other bounce
+    let closing = (first_velocity[0] - second_velocity[0]) * normal[0]
+        + (first_velocity[1] - second_velocity[1]) * normal[1];
+
+    if closing < 0.0 {
+        let impulse = -(1.0 + RESTITUTION) * closing / 2.0;
+
+        first.state.set_velocity_from_impact([
+            first_velocity[0] + impulse * normal[0],
+            first_velocity[1] + impulse * normal[1],
+        ]);
+        second.state.set_velocity_from_impact([
+            second_velocity[0] - impulse * normal[0],
+            second_velocity[1] - impulse * normal[1],
+        ]);
+    }
+
+    return true;
+}
+
+// Whether another vehicle is low enough, or high enough, to be seen or hit
+pub fn can_meet(first: &VehicleState, second: &VehicleState) -> bool {
+    return (first.height - second.height).abs() < VEHICLE_HEIGHT;
+}
+
+// Moves the vehicle along away, a unit vector, and takes out any velocity
+// into the surface it hit
+fn push_out(state: &mut VehicleState, away: [f64; 2], depth: f64) {
+    state.x += away[0] * depth;
+    state.y += away[1] * depth;
+
+    let current = state.velocity();
+    let normal = [away[0] as f32, away[1] as f32];
+    let into = current[0] * normal[0] + current[1] * normal[1];
+
+    if into < 0.0 {
+        let change = -(1.0 + RESTITUTION) * into;
+
+        state.set_velocity_from_impact([
+            current[0] + change * normal[0],
+            current[1] + change * normal[1],
+        ]);
+    }
+}
+
+// Distance between the points and the unit vector from the first to the
+// second. Points on top of each other are pushed apart northwards.
+fn offset(from: [f64; 2], to: [f64; 2]) -> (f64, [f64; 2]) {
+    let delta = [to[0] - from[0], to[1] - from[1]];
+    let distance = (delta[0].powi(2) + delta[1].powi(2)).sqrt();
+
+    if distance < 1e-9 {
+        return (0.0, [0.0, 1.0]);
+    }
+
+    return (distance, [delta[0] / distance, delta[1] / distance]);
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct EscConfig {
+    // Degrees celsius
+    pub ambient_temperature: f32,
+    // Degrees per second for each amp squared through the ESC
+    pub heating: f32,
+    // Seconds for the ESC to shed most of its heat back to ambient
+    pub cooling_time: f32,
+}
+
+impl Default for EscConfig {
+    fn default() -> Self {
+        return EscConfig {
+            ambient_temperature: 25.0,
+            heating: 0.0004,
+            cooling_time: 120.0,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Esc {
+    pub config: EscConfig,
+    pub temperature: f32,
+}
+
+impl Esc {
+    pub fn new(config: EscConfig) -> Self {
+        return Esc {
+            temperature: config.ambient_temperature,
+            config,
+        };
+    }
+
+    pub fn step(&mut self, motor_current: f32, dt: f32) {
+        let heating = self.config.heating * motor_current.powi(2);
+
+        let cooling = if self.config.cooling_time > 0.0 {
+            (self.temperature - self.config.ambient_temperature) / self.config.cooling_time
+        } else {
+            0.0
+        };
+
+        self.temperature += (heating - cooling) * dt;
+    }
+}
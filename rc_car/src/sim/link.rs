@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Distribution, Normal};
+
+// One direction of a bad network between the driver and the car. All zero
+// passes packets straight through.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LinkConfig {
+    // ms added to every packet
+    pub latency: f32,
+    // Standard deviation in ms of random latency on top, packets can
+    // overtake each other
+    pub jitter: f32,
+    // Fraction of packets dropped, 0.0 to 1.0
+    pub loss: f32,
+    // Fraction of packets delivered twice
+    pub duplication: f32,
+    // Fraction of packets held back an extra reorder_delay ms, so the ones
+    // after them arrive first
+    pub reorder: f32,
+    pub reorder_delay: f32,
+    // Bytes per second, packets queue behind each other. Unset is no cap.
+    pub bandwidth: Option<u32>,
+    // Bytes waiting on the bandwidth cap before new packets are dropped
+    pub queue_limit: u32,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        return LinkConfig {
+            latency: 0.0,
+            jitter: 0.0,
+            loss: 0.0,
+            duplication: 0.0,
+            reorder: 0.0,
+            reorder_delay: 50.0,
+            bandwidth: None,
+            queue_limit: 1000,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Link {
+    pub config: LinkConfig,
+    // Packets the link has thrown away, lost or over the queue limit
+    pub dropped: u64,
+    rng: ChaCha8Rng,
+    // Seconds since the link was made
+    clock: f64,
+    // When the bandwidth cap is free for the next packet
+    busy_until: f64,
+    // Arrival time and packet, in the order they were sent
+    in_flight: Vec<(f64, Vec<u8>)>,
+}
+
+impl Link {
+    // stream picks one of the seed's independent sequences, so links can
+    // share the sim's seed without drawing the same numbers as it
+    pub fn new(config: LinkConfig, seed: u64, stream: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        rng.set_stream(stream);
+
+        return Link {
+            config,
+            dropped: 0,
+            rng,
+            clock: 0.0,
+            busy_until: 0.0,
+            in_flight: Vec::new(),
+        };
+    }
+
+    pub fn send(&mut self, packet: &[u8]) {
+        if self.rng.gen::<f32>() < self.config.loss {
+            self.dropped += 1;
+            return;
+        }
+
+        let copies = if self.rng.gen::<f32>() < self.config.duplication {
+            2
+        } else {
+            1
+        };
+
+        for _ in 0..copies {
+            let mut leaves = self.clock;
+
+            if let Some(bandwidth) = self.config.bandwidth {
+                let bandwidth = f64::from(bandwidth.max(1));
+                let start = self.busy_until.max(self.clock);
+
+                if (start - self.clock) * bandwidth > f64::from(self.config.queue_limit) {
+                    self.dropped += 1;
+                    continue;
+                }
+
+                self.busy_until = start + packet.len() as f64 / bandwidth;
+                leaves = self.busy_until;
+            }
+
+            let jitter = match Normal::new(0.0, self.config.jitter.max(0.0)) {
+                Ok(n) => n.sample(&mut self.rng),
+                Err(_) => 0.0,
+            };
+
+            let mut delay = (self.config.latency + jitter).max(0.0);
+
+            if self.rng.gen::<f32>() < self.config.reorder {
+                delay += self.config.reorder_delay.max(0.0);
+            }
+
+            self.in_flight
+                .push((leaves + f64::from(delay) / 1000.0, packet.to_vec()));
+        }
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        self.clock += f64::from(dt);
+    }
+
+    // Packets that have arrived, in the order they arrived
+    pub fn receive(&mut self) -> Vec<Vec<u8>> {
+        let clock = self.clock;
+
+        let mut arrived: Vec<(f64, Vec<u8>)> = Vec::new();
+        let mut index = 0;
+
+        while index < self.in_flight.len() {
+            if self.in_flight[index].0 <= clock {
+                arrived.push(self.in_flight.remove(index));
+            } else {
+                index += 1;
+            }
+        }
+
+        arrived.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        return arrived.into_iter().map(|(_, p)| p).collect();
+    }
+}
@@ -0,0 +1,147 @@
+pub mod battery;
+pub mod collision;
+pub mod esc;
+pub mod link;
+pub mod radio;
+pub mod sensors;
+pub mod vehicle;
+pub mod world;
+
+use battery::{Battery, BatteryConfig};
+use esc::{Esc, EscConfig};
+use link::{Link, LinkConfig};
+use radio::{Radio, RadioConfig};
+use sensors::{SensorConfig, Sensors};
+use vehicle::{Vehicle, VehicleConfig};
+use world::{Obstacle, World};
+
+use common_data::server::data::position::Position;
+
+use serde::{Deserialize, Serialize};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SimConfig {
+    // Latitude and longitude of the sim origin in degrees
+    pub gps_origin: [f64; 2],
+    // All sim randomness comes from this seed so runs can be repeated
+    pub seed: u64,
+    pub vehicle: VehicleConfig,
+    pub battery: BatteryConfig,
+    pub esc: EscConfig,
+    pub radio: RadioConfig,
+    pub sensors: SensorConfig,
+    // Network between the driver and the car, driver to car and car to driver
+    pub uplink: LinkConfig,
+    pub downlink: LinkConfig,
+    // World file to load relative to the car config, replaces world when set
+    pub world_file: Option<String>,
+    pub world: World,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        return SimConfig {
+            gps_origin: [-33.8688, 151.2093],
+            seed: 1,
+            vehicle: VehicleConfig::default(),
+            battery: BatteryConfig::default(),
+            esc: EscConfig::default(),
+            radio: RadioConfig::default(),
+            sensors: SensorConfig::default(),
+            uplink: LinkConfig::default(),
+            downlink: LinkConfig::default(),
+            world_file: None,
+            world: World::default(),
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub config: SimConfig,
+    pub vehicle: Vehicle,
+    pub battery: Battery,
+    pub esc: Esc,
+    pub radio: Radio,
+    pub sensors: Sensors,
+    pub uplink: Link,
+    pub downlink: Link,
+    // Other vehicles sharing the world, set by the fleet before each step
+    pub traffic: Vec<Obstacle>,
+    rng: ChaCha8Rng,
+}
+
+impl Simulation {
+    pub fn new(mut config: SimConfig) -> Self {
+        if let Some(origin) = config.world.gps_origin {
+            config.gps_origin = origin;
+        }
+
+        let mut vehicle = Vehicle::new(config.vehicle.clone());
+        vehicle.state.x = config.world.start.east;
+        vehicle.state.y = config.world.start.north;
+        vehicle.state.heading = config
+            .world
+            .start
+            .heading
+            .to_radians()
+            .rem_euclid(std::f32::consts::TAU);
+
+        return Simulation {
+            vehicle,
+            battery: Battery::new(config.battery.clone()),
+            esc: Esc::new(config.esc.clone()),
+            radio: Radio::new(config.radio.clone()),
+            sensors: Sensors::new(config.sensors.clone()),
+            // Own streams so impairing the link does not change the sensor
+            // noise, and fleet cars on neighbouring seeds do not share draws
+            uplink: Link::new(config.uplink.clone(), config.seed, 1),
+            downlink: Link::new(config.downlink.clone(), config.seed, 2),
+            traffic: Vec::new(),
+            rng: ChaCha8Rng::seed_from_u64(config.seed),
+            config,
+        };
+    }
+
+    pub fn step(&mut self, throttle: f32, steering: f32, dt: f32) {
+        // A flat pack cannot drive the motor
+        let throttle = if self.battery.is_empty() {
+            0.0
+        } else {
+            throttle
+        };
+
+        self.battery.step(throttle, dt);
+        let friction = self
+            .config
+            .world
+            .friction_at([self.vehicle.state.x, self.vehicle.state.y]);
+        self.vehicle.step(throttle, steering, friction, dt);
+
+        collision::collide_world(&mut self.vehicle, &self.config.world);
+
+        self.esc.step(self.battery.motor_current_ma / 1000.0, dt);
+
+        self.sensors.step(
+            &mut self.rng,
+            &self.vehicle,
+            &self.config.world,
+            &self.traffic,
+            dt,
+        );
+        self.radio.step(&mut self.rng, &self.vehicle);
+    }
+
+    pub fn origin(&self) -> Position {
+        return Position::new(self.config.gps_origin[0], self.config.gps_origin[1], 0.0);
+    }
+
+    // Converts meters east and north of the origin into a position
+    pub fn local_to_position(&self, east: f64, north: f64) -> Position {
+        return Position::from_enu(&self.origin(), [east, north, 0.0]);
+    }
+}
@@ -0,0 +1,62 @@
+use crate::sim::vehicle::Vehicle;
+
+use serde::{Deserialize, Serialize};
+
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Distribution, Normal};
+
+// Log distance path loss from a base station at the sim origin
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RadioConfig {
+    pub tx_power: f32,
+    // dB lost over the first meter
+    pub reference_loss: f32,
+    // 2.0 in free space, higher with ground clutter
+    pub path_loss_exponent: f32,
+    // Standard deviation of fading in dB
+    pub fading_std: f32,
+}
+
+impl Default for RadioConfig {
+    fn default() -> Self {
+        return RadioConfig {
+            tx_power: 20.0,
+            reference_loss: 40.0,
+            path_loss_exponent: 2.7,
+            fading_std: 2.0,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Radio {
+    pub config: RadioConfig,
+    // dBm
+    pub signal_strength: f32,
+}
+
+impl Radio {
+    pub fn new(config: RadioConfig) -> Self {
+        return Radio {
+            signal_strength: config.tx_power - config.reference_loss,
+            config,
+        };
+    }
+
+    pub fn step(&mut self, rng: &mut ChaCha8Rng, vehicle: &Vehicle) {
+        let distance = (vehicle.state.x.powi(2) + vehicle.state.y.powi(2))
+            .sqrt()
+            .max(1.0) as f32;
+
+        let fading = match Normal::new(0.0, self.config.fading_std.max(0.0)) {
+            Ok(n) => n.sample(rng),
+            Err(_) => 0.0,
+        };
+
+        self.signal_strength = self.config.tx_power
+            - self.config.reference_loss
+            - 10.0 * self.config.path_loss_exponent * distance.log10()
+            + fading;
+    }
+}
@@ -0,0 +1,303 @@
+use crate::sim::vehicle::Vehicle;
+use crate::sim::world::{Obstacle, World};
+
+use serde::{Deserialize, Serialize};
+
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Distribution, Normal};
+
+const GRAVITY: f32 = 9.81;
+
+// Noise model for one sensor. noise_std and bias are in the unit of the
+// sensor: meters for GPS, degrees for heading.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct NoiseConfig {
+    pub rate_hz: f32,
+    pub noise_std: f32,
+    pub bias: f32,
+    // Chance of each update being the start of a dropout
+    pub dropout_probability: f32,
+    // Seconds a dropout lasts
+    pub dropout_duration: f32,
+}
+
+impl Default for NoiseConfig {
+    fn default() -> Self {
+        return NoiseConfig {
+            rate_hz: 10.0,
+            noise_std: 0.0,
+            bias: 0.0,
+            dropout_probability: 0.0,
+            dropout_duration: 0.0,
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SensorConfig {
+    pub gps: NoiseConfig,
+    pub heading: NoiseConfig,
+    // Speed from the wheel encoder in m/s
+    pub wheel_speed: NoiseConfig,
+    // m/s^2, applied to each axis
+    pub accelerometer: NoiseConfig,
+    pub range: Vec<RangeSensorConfig>,
+}
+
+// Ultrasonic or lidar beam against the obstacles in the world
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RangeSensorConfig {
+    // Degrees clockwise from straight ahead
+    pub angle: f32,
+    // Meters in front of the car's reference point
+    pub mount_forward: f32,
+    pub max_range: f32,
+    // Meters
+    pub noise: NoiseConfig,
+}
+
+impl Default for RangeSensorConfig {
+    fn default() -> Self {
+        return RangeSensorConfig {
+            angle: 0.0,
+            mount_forward: 0.3,
+            max_range: 15.0,
+            noise: NoiseConfig {
+                rate_hz: 20.0,
+                noise_std: 0.02,
+                bias: 0.0,
+                dropout_probability: 0.0,
+                dropout_duration: 0.0,
+            },
+        };
+    }
+}
+
+impl Default for SensorConfig {
+    fn default() -> Self {
+        return SensorConfig {
+            gps: NoiseConfig {
+                rate_hz: 5.0,
+                noise_std: 1.5,
+                bias: 0.5,
+                dropout_probability: 0.01,
+                dropout_duration: 2.0,
+            },
+            heading: NoiseConfig {
+                rate_hz: 50.0,
+                noise_std: 2.0,
+                bias: 1.0,
+                dropout_probability: 0.0,
+                dropout_duration: 0.0,
+            },
+            wheel_speed: NoiseConfig {
+                rate_hz: 50.0,
+                noise_std: 0.05,
+                bias: 0.0,
+                dropout_probability: 0.0,
+                dropout_duration: 0.0,
+            },
+            accelerometer: NoiseConfig {
+                rate_hz: 50.0,
+                noise_std: 0.2,
+                bias: 0.05,
+                dropout_probability: 0.0,
+                dropout_duration: 0.0,
+            },
+            range: vec![
+                RangeSensorConfig {
+                    angle: -20.0,
+                    ..RangeSensorConfig::default()
+                },
+                RangeSensorConfig::default(),
+                RangeSensorConfig {
+                    angle: 20.0,
+                    ..RangeSensorConfig::default()
+                },
+            ],
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SensorChannel {
+    pub config: NoiseConfig,
+    since_update: f32,
+    dropout_left: f32,
+}
+
+impl SensorChannel {
+    pub fn new(config: NoiseConfig) -> Self {
+        return SensorChannel {
+            config,
+            // Due on the first step so readings exist straight away
+            since_update: f32::MAX,
+            dropout_left: 0.0,
+        };
+    }
+
+    // Returns true when a new sample should be taken this step
+    pub fn update(&mut self, rng: &mut ChaCha8Rng, dt: f32) -> bool {
+        self.since_update += dt;
+        self.dropout_left = (self.dropout_left - dt).max(0.0);
+
+        // Small allowance so a sensor running at the tick rate is not skipped
+        // by float rounding
+        if self.config.rate_hz <= 0.0 || self.since_update < 0.999 / self.config.rate_hz {
+            return false;
+        }
+
+        self.since_update = 0.0;
+
+        if self.dropout_left > 0.0 {
+            return false;
+        }
+
+        if self.config.dropout_probability > 0.0
+            && rng.gen::<f32>() < self.config.dropout_probability
+        {
+            self.dropout_left = self.config.dropout_duration;
+            return false;
+        }
+
+        return true;
+    }
+
+    pub fn is_dropped_out(&self) -> bool {
+        return self.dropout_left > 0.0;
+    }
+
+    pub fn noise(&self, rng: &mut ChaCha8Rng) -> f32 {
+        let gaussian = match Normal::new(0.0, self.config.noise_std.max(0.0)) {
+            Ok(n) => n.sample(rng),
+            Err(_) => 0.0,
+        };
+
+        return self.config.bias + gaussian;
+    }
+}
+
+// Last values the sensors reported. These are held between updates and
+// through dropouts, like the real receivers do.
+#[derive(Debug, Clone)]
+pub struct SensorReadings {
+    // Meters east and north of the sim origin
+    pub gps: Option<[f64; 2]>,
+    pub gps_valid: bool,
+    // Degrees clockwise from north
+    pub heading: Option<f32>,
+    pub wheel_speed: Option<f32>,
+    // m/s^2 forward, right, up. Up includes gravity like a real accelerometer.
+    pub acceleration: Option<[f32; 3]>,
+    // Meters for each range sensor, None when nothing is within range
+    pub ranges: Vec<Option<f32>>,
+    // Set on the steps a sensor produced a new sample
+    pub gps_updated: bool,
+    pub heading_updated: bool,
+    pub wheel_speed_updated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sensors {
+    pub gps: SensorChannel,
+    pub heading: SensorChannel,
+    pub wheel_speed: SensorChannel,
+    pub accelerometer: SensorChannel,
+    pub range: Vec<(RangeSensorConfig, SensorChannel)>,
+    pub readings: SensorReadings,
+}
+
+impl Sensors {
+    pub fn new(config: SensorConfig) -> Self {
+        return Sensors {
+            gps: SensorChannel::new(config.gps),
+            heading: SensorChannel::new(config.heading),
+            wheel_speed: SensorChannel::new(config.wheel_speed),
+            accelerometer: SensorChannel::new(config.accelerometer),
+            readings: SensorReadings {
+                gps: None,
+                gps_valid: false,
+                heading: None,
+                wheel_speed: None,
+                acceleration: None,
+                ranges: vec![None; config.range.len()],
+                gps_updated: false,
+                heading_updated: false,
+                wheel_speed_updated: false,
+            },
+            range: config
+                .range
+                .into_iter()
+                .map(|r| {
+                    let channel = SensorChannel::new(r.noise.clone());
+                    (r, channel)
+                })
+                .collect(),
+        };
+    }
+
+    pub fn step(
+        &mut self,
+        rng: &mut ChaCha8Rng,
+        vehicle: &Vehicle,
+        world: &World,
+        traffic: &[Obstacle],
+        dt: f32,
+    ) {
+        self.readings.gps_updated = self.gps.update(rng, dt);
+
+        if self.readings.gps_updated {
+            let east = vehicle.state.x + f64::from(self.gps.noise(rng));
+            let north = vehicle.state.y + f64::from(self.gps.noise(rng));
+
+            self.readings.gps = Some([east, north]);
+        }
+
+        self.readings.gps_valid = self.readings.gps.is_some() && !self.gps.is_dropped_out();
+
+        self.readings.heading_updated = self.heading.update(rng, dt);
+
+        if self.readings.heading_updated {
+            let measured = vehicle.state.heading.to_degrees() + self.heading.noise(rng);
+
+            self.readings.heading = Some(measured.rem_euclid(360.0));
+        }
+
+        self.readings.wheel_speed_updated = self.wheel_speed.update(rng, dt);
+
+        if self.readings.wheel_speed_updated {
+            self.readings.wheel_speed = Some(vehicle.state.speed + self.wheel_speed.noise(rng));
+        }
+
+        if self.accelerometer.update(rng, dt) {
+            self.readings.acceleration = Some([
+                vehicle.state.acceleration[0] + self.accelerometer.noise(rng),
+                vehicle.state.acceleration[1] + self.accelerometer.noise(rng),
+                GRAVITY + self.accelerometer.noise(rng),
+            ]);
+        }
+
+        let heading = f64::from(vehicle.state.heading);
+
+        for (index, (config, channel)) in self.range.iter_mut().enumerate() {
+            if !channel.update(rng, dt) {
+                continue;
+            }
+
+            let mount = f64::from(config.mount_forward);
+            let origin = [
+                vehicle.state.x + mount * heading.sin(),
+                vehicle.state.y + mount * heading.cos(),
+            ];
+            let angle = heading + f64::from(config.angle).to_radians();
+
+            self.readings.ranges[index] = world
+                .ray_cast(origin, angle, f64::from(config.max_range), traffic)
+                .map(|d| (d as f32 + channel.noise(rng)).max(0.0));
+        }
+    }
+}
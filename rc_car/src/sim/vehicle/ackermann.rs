@@ -0,0 +1,241 @@
+use crate::sim::vehicle::{VehicleConfig, VehicleModel, VehicleState, GRAVITY};
+
+use common_data::server::data::capabilities::Axis;
+
+use serde::{Deserialize, Serialize};
+
+// Below this forward speed in m/s the dynamic model falls back to the
+// kinematic one, tire slip is not defined at a standstill
+const DYNAMIC_MIN_SPEED: f32 = 1.0;
+
+// Longest step the dynamic model integrates at once, the tire forces are stiff
+const DYNAMIC_MAX_STEP: f32 = 0.002;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum AckermannModel {
+    // Wheels never slip, fine for slow cars
+    Kinematic,
+    // Single track with tire slip and weight transfer, for drift cars
+    Dynamic,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DynamicConfig {
+    // kg
+    pub mass: f32,
+    // kg m^2 about the vertical axis
+    pub yaw_inertia: f32,
+    // Meters from the center of mass to the front axle, the rest of the
+    // wheelbase is behind it
+    pub cg_to_front: f32,
+    pub cg_height: f32,
+    // Newtons at the rear wheels at full throttle and full braking
+    pub max_drive_force: f32,
+    pub max_brake_force: f32,
+    // Pacejka magic formula stiffness, shape and curvature factors
+    pub tire_b: f32,
+    pub tire_c: f32,
+    pub tire_e: f32,
+}
+
+impl Default for DynamicConfig {
+    fn default() -> Self {
+        return DynamicConfig {
+            mass: 1.8,
+            yaw_inertia: 0.03,
+            cg_to_front: 0.14,
+            cg_height: 0.06,
+            max_drive_force: 15.0,
+            max_brake_force: 20.0,
+            tire_b: 8.0,
+            tire_c: 1.4,
+            tire_e: -0.5,
+        };
+    }
+}
+
+// Car steered by its front wheels. Throttle sets the target speed and
+// steering the angle of the front wheels.
+#[derive(Debug, Clone)]
+pub struct Ackermann {
+    pub config: VehicleConfig,
+}
+
+impl Ackermann {
+    pub fn new(config: VehicleConfig) -> Self {
+        return Ackermann { config };
+    }
+
+    fn step_kinematic(&self, state: &mut VehicleState, target_speed: f32, friction: f32, dt: f32) {
+        // Less grip means the wheels spin or lock sooner
+        let max_change = self.config.acceleration * friction.min(1.0) * dt;
+        let speed_change = (target_speed - state.speed).clamp(-max_change, max_change);
+        state.speed += speed_change;
+
+        let mut yaw_rate = state.speed / self.config.wheelbase * state.steering_angle.tan();
+
+        // Past the grip limit the front slides and the car turns less than
+        // the wheels point
+        let max_lateral = friction * GRAVITY;
+        if state.speed.abs() > 0.0 && (state.speed * yaw_rate).abs() > max_lateral {
+            yaw_rate = max_lateral / state.speed.abs() * yaw_rate.signum();
+        }
+
+        state.yaw_rate = yaw_rate;
+        state.lateral_speed = 0.0;
+        state.slip_angle = 0.0;
+
+        state.heading = (state.heading + yaw_rate * dt).rem_euclid(std::f32::consts::TAU);
+
+        if dt > 0.0 {
+            state.acceleration = [speed_change / dt, state.speed * yaw_rate];
+        }
+
+        state.advance(dt);
+    }
+
+    fn step_dynamic(&self, state: &mut VehicleState, target_speed: f32, friction: f32, dt: f32) {
+        let config = &self.config.dynamic;
+
+        let mass = config.mass.max(0.01);
+        let wheelbase = self.config.wheelbase.max(0.01);
+        let to_front = config.cg_to_front.clamp(0.0, wheelbase);
+        let to_rear = wheelbase - to_front;
+
+        // Braking and accelerating moves weight between the axles
+        let transfer = mass * state.acceleration[0] * config.cg_height / wheelbase;
+        let front_load = (mass * GRAVITY * to_rear / wheelbase - transfer).max(0.0);
+        let rear_load = (mass * GRAVITY * to_front / wheelbase + transfer).max(0.0);
+
+        // The ESC drives the rear wheels towards the target speed, braking
+        // is shared between the axles by how much weight is on them
+        let demand = ((target_speed - state.speed) / 0.5).clamp(-1.0, 1.0);
+        let (front_long, rear_long) = if demand >= 0.0 {
+            (0.0, demand * config.max_drive_force)
+        } else {
+            let brake = demand * config.max_brake_force;
+            let total_load = (front_load + rear_load).max(0.0001);
+            (
+                brake * front_load / total_load,
+                brake * rear_load / total_load,
+            )
+        };
+
+        // Force beyond the grip spins or locks the wheels, and a tire that
+        // is sliding has little left to hold the car in line
+        let (front_long, front_lateral_grip) = friction_circle(front_long, friction * front_load);
+        let (rear_long, rear_lateral_grip) = friction_circle(rear_long, friction * rear_load);
+
+        let front_slip = state.steering_angle
+            - (state.lateral_speed + to_front * state.yaw_rate).atan2(state.speed);
+        let rear_slip = -(state.lateral_speed - to_rear * state.yaw_rate).atan2(state.speed);
+
+        let front_force = (pacejka(front_slip, config) * friction * front_load)
+            .clamp(-front_lateral_grip, front_lateral_grip);
+        let rear_force = (pacejka(rear_slip, config) * friction * rear_load)
+            .clamp(-rear_lateral_grip, rear_lateral_grip);
+
+        let (sin_steer, cos_steer) = state.steering_angle.sin_cos();
+
+        // The front wheels point along the steering angle
+        let front_x = front_long * cos_steer - front_force * sin_steer;
+        let front_y = front_long * sin_steer + front_force * cos_steer;
+
+        let forward_acceleration = (front_x + rear_long) / mass;
+        let lateral_acceleration = (front_y + rear_force) / mass;
+        let yaw_acceleration =
+            (to_front * front_y - to_rear * rear_force) / config.yaw_inertia.max(0.0001);
+
+        state.speed += (forward_acceleration + state.lateral_speed * state.yaw_rate) * dt;
+        state.lateral_speed += (lateral_acceleration - state.speed * state.yaw_rate) * dt;
+        state.yaw_rate += yaw_acceleration * dt;
+
+        state.acceleration = [forward_acceleration, lateral_acceleration];
+        state.slip_angle = state.lateral_speed.atan2(state.speed.abs());
+
+        state.heading = (state.heading + state.yaw_rate * dt).rem_euclid(std::f32::consts::TAU);
+
+        state.advance(dt);
+    }
+}
+
+impl VehicleModel for Ackermann {
+    fn step(
+        &mut self,
+        state: &mut VehicleState,
+        throttle: f32,
+        steering: f32,
+        friction: f32,
+        dt: f32,
+    ) {
+        let target_speed = if throttle >= 0.0 {
+            throttle * self.config.max_speed
+        } else {
+            throttle * self.config.max_reverse_speed
+        };
+
+        state.steering_angle = steering * self.config.max_steering_angle.to_radians();
+
+        if self.config.model == AckermannModel::Kinematic || state.speed < DYNAMIC_MIN_SPEED {
+            self.step_kinematic(state, target_speed, friction, dt);
+        } else {
+            let steps = (dt / DYNAMIC_MAX_STEP).ceil().max(1.0);
+
+            for _ in 0..steps as u32 {
+                self.step_dynamic(state, target_speed, friction, dt / steps);
+            }
+        }
+    }
+
+    fn yaw_rate(&self, speed: f32, steering: f32) -> f32 {
+        if self.config.wheelbase <= 0.0 {
+            return 0.0;
+        }
+
+        let steering_angle = steering * self.config.max_steering_angle.to_radians();
+
+        return speed * steering_angle.tan() / self.config.wheelbase;
+    }
+
+    fn steering_for_curvature(&self, curvature: f32, _speed: f32) -> f32 {
+        let max_steering_angle = self.config.max_steering_angle.to_radians();
+
+        if max_steering_angle <= 0.0 {
+            return 0.0;
+        }
+
+        let steering_angle = (curvature * self.config.wheelbase).atan();
+
+        return (steering_angle / max_steering_angle).clamp(-1.0, 1.0);
+    }
+
+    fn axes(&self) -> Vec<Axis> {
+        return vec![Axis::Accelerate, Axis::Steering];
+    }
+
+    fn slips(&self) -> bool {
+        return self.config.model == AckermannModel::Dynamic;
+    }
+
+    fn clone_box(&self) -> Box<dyn VehicleModel> {
+        return Box::new(self.clone());
+    }
+}
+
+// Longitudinal force the tire can actually deliver and the lateral force it
+// has left, given the most it can grip in any direction
+fn friction_circle(longitudinal: f32, grip: f32) -> (f32, f32) {
+    let longitudinal = longitudinal.clamp(-grip, grip);
+    let lateral = (grip.powi(2) - longitudinal.powi(2)).max(0.0).sqrt();
+
+    return (longitudinal, lateral.max(0.2 * grip));
+}
+
+// Lateral force per newton of load for a slip angle in radians
+fn pacejka(slip: f32, config: &DynamicConfig) -> f32 {
+    let stiffness = config.tire_b * slip;
+
+    return (config.tire_c * (stiffness - config.tire_e * (stiffness - stiffness.atan())).atan())
+        .sin();
+}
@@ -0,0 +1,107 @@
+use crate::sim::vehicle::{VehicleConfig, VehicleModel, VehicleState};
+
+use common_data::server::data::capabilities::Axis;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BoatConfig {
+    // How fast sideways drift dies away, per second
+    pub lateral_drag: f32,
+    // Yaw rate in radians per second for each m/s of speed at full rudder,
+    // the rudder does nothing without water flowing past it
+    pub rudder_rate: f32,
+    // How fast the hull follows the rudder, per second
+    pub yaw_response: f32,
+}
+
+impl Default for BoatConfig {
+    fn default() -> Self {
+        return BoatConfig {
+            lateral_drag: 4.0,
+            rudder_rate: 0.8,
+            yaw_response: 3.0,
+        };
+    }
+}
+
+// Propeller and rudder. Throttle sets the thrust and steering the rudder,
+// water drag sets the top speed and the hull drifts wide in turns.
+#[derive(Debug, Clone)]
+pub struct Boat {
+    pub config: VehicleConfig,
+}
+
+impl Boat {
+    pub fn new(config: VehicleConfig) -> Self {
+        return Boat { config };
+    }
+}
+
+impl VehicleModel for Boat {
+    // Water is the same everywhere, surface friction is ignored
+    fn step(
+        &mut self,
+        state: &mut VehicleState,
+        throttle: f32,
+        steering: f32,
+        _friction: f32,
+        dt: f32,
+    ) {
+        let max_speed = self.config.max_speed.max(0.01);
+
+        // Drag grows with the square of speed and matches full thrust at
+        // top speed
+        let thrust = if throttle >= 0.0 {
+            throttle * self.config.acceleration
+        } else {
+            throttle
+                * self.config.acceleration
+                * (self.config.max_reverse_speed / max_speed).powi(2)
+        };
+        let drag = self.config.acceleration * state.speed * state.speed.abs() / max_speed.powi(2);
+
+        let forward_acceleration = thrust - drag;
+        let lateral_acceleration = -state.lateral_speed * self.config.boat.lateral_drag;
+
+        let target_yaw_rate = self.yaw_rate(state.speed, steering);
+        state.yaw_rate +=
+            (target_yaw_rate - state.yaw_rate) * (self.config.boat.yaw_response * dt).min(1.0);
+
+        state.speed += (forward_acceleration + state.lateral_speed * state.yaw_rate) * dt;
+        state.lateral_speed += (lateral_acceleration - state.speed * state.yaw_rate) * dt;
+
+        state.acceleration = [forward_acceleration, lateral_acceleration];
+        state.steering_angle = steering * self.config.max_steering_angle.to_radians();
+        state.slip_angle = state.lateral_speed.atan2(state.speed.abs());
+
+        state.heading = (state.heading + state.yaw_rate * dt).rem_euclid(std::f32::consts::TAU);
+
+        state.advance(dt);
+    }
+
+    fn yaw_rate(&self, speed: f32, steering: f32) -> f32 {
+        return steering * self.config.boat.rudder_rate * speed;
+    }
+
+    fn steering_for_curvature(&self, curvature: f32, _speed: f32) -> f32 {
+        if self.config.boat.rudder_rate <= 0.0 {
+            return 0.0;
+        }
+
+        return (curvature / self.config.boat.rudder_rate).clamp(-1.0, 1.0);
+    }
+
+    fn axes(&self) -> Vec<Axis> {
+        return vec![Axis::Accelerate, Axis::Steering];
+    }
+
+    fn slips(&self) -> bool {
+        return true;
+    }
+
+    fn clone_box(&self) -> Box<dyn VehicleModel> {
+        return Box::new(self.clone());
+    }
+}
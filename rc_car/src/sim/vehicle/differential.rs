@@ -0,0 +1,213 @@
+use crate::sim::vehicle::{VehicleConfig, VehicleModel, VehicleState};
+
+use common_data::server::data::capabilities::Axis;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DifferentialConfig {
+    // Meters between the left and right wheels or tracks
+    pub track_width: f32,
+    // How much of the speed difference between the sides turns the vehicle,
+    // tracks scrub and turn less than wheels
+    pub turn_efficiency: f32,
+}
+
+impl Default for DifferentialConfig {
+    fn default() -> Self {
+        return DifferentialConfig {
+            track_width: 0.25,
+            turn_efficiency: 0.8,
+        };
+    }
+}
+
+// Skid steered robot or tank. Throttle drives both sides, steering speeds
+// one side up and slows the other, so it can turn on the spot.
+#[derive(Debug, Clone)]
+pub struct Differential {
+    pub config: VehicleConfig,
+    // m/s of each side
+    left: f32,
+    right: f32,
+}
+
+impl Differential {
+    pub fn new(config: VehicleConfig) -> Self {
+        return Differential {
+            config,
+            left: 0.0,
+            right: 0.0,
+        };
+    }
+
+    fn side_speed(&self, command: f32) -> f32 {
+        let command = command.clamp(-1.0, 1.0);
+
+        if command >= 0.0 {
+            return command * self.config.max_speed;
+        }
+
+        return command * self.config.max_reverse_speed;
+    }
+
+    // Throttle that settles at this speed with this steering. Speed only
+    // grows with throttle, so it is found by halving.
+    fn throttle_for_speed(&self, speed: f32, steering: f32) -> f32 {
+        let (mut low, mut high) = (-1.0f32, 1.0f32);
+
+        for _ in 0..24 {
+            let throttle = (low + high) / 2.0;
+            let settled =
+                (self.side_speed(throttle + steering) + self.side_speed(throttle - steering)) / 2.0;
+
+            if settled < speed {
+                low = throttle;
+            } else {
+                high = throttle;
+            }
+        }
+
+        return (low + high) / 2.0;
+    }
+
+    fn track_width(&self) -> f32 {
+        return self.config.differential.track_width.max(0.01);
+    }
+}
+
+impl VehicleModel for Differential {
+    fn step(
+        &mut self,
+        state: &mut VehicleState,
+        throttle: f32,
+        steering: f32,
+        friction: f32,
+        dt: f32,
+    ) {
+        // Collisions change the speed from outside, the sides carry on from
+        // what is left of it
+        let shift = state.speed - (self.left + self.right) / 2.0;
+        self.left += shift;
+        self.right += shift;
+
+        let max_change = self.config.acceleration * friction.min(1.0) * dt;
+
+        let left_target = self.side_speed(throttle + steering);
+        let right_target = self.side_speed(throttle - steering);
+
+        self.left += (left_target - self.left).clamp(-max_change, max_change);
+        self.right += (right_target - self.right).clamp(-max_change, max_change);
+
+        let speed = (self.left + self.right) / 2.0;
+        let yaw_rate = (self.left - self.right) / self.track_width()
+            * self.config.differential.turn_efficiency;
+
+        if dt > 0.0 {
+            state.acceleration = [(speed - state.speed) / dt, speed * yaw_rate];
+        }
+
+        state.speed = speed;
+        state.lateral_speed = 0.0;
+        state.yaw_rate = yaw_rate;
+        state.slip_angle = 0.0;
+        state.steering_angle = 0.0;
+
+        state.heading = (state.heading + yaw_rate * dt).rem_euclid(std::f32::consts::TAU);
+
+        state.advance(dt);
+    }
+
+    // The throttle is taken to be what holds the speed, so each side runs
+    // as step would drive it
+    fn yaw_rate(&self, speed: f32, steering: f32) -> f32 {
+        let throttle = self.throttle_for_speed(speed, steering);
+
+        let left = self.side_speed(throttle + steering);
+        let right = self.side_speed(throttle - steering);
+
+        return (left - right) / self.track_width() * self.config.differential.turn_efficiency;
+    }
+
+    fn steering_for_curvature(&self, curvature: f32, speed: f32) -> f32 {
+        let per_steering = self.yaw_rate(speed, 1.0);
+
+        if per_steering <= 0.0 {
+            return 0.0;
+        }
+
+        return (curvature * speed / per_steering).clamp(-1.0, 1.0);
+    }
+
+    fn axes(&self) -> Vec<Axis> {
+        return vec![Axis::Accelerate, Axis::Yaw];
+    }
+
+    fn slips(&self) -> bool {
+        return false;
+    }
+
+    fn clone_box(&self) -> Box<dyn VehicleModel> {
+        return Box::new(self.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::collision::collide_world;
+    use crate::sim::vehicle::{Vehicle, VehicleKind};
+    use crate::sim::world::{Obstacle, World};
+
+    #[test]
+    fn predicted_yaw_rate_matches_the_plant() {
+        let config = VehicleConfig {
+            max_speed: 4.0,
+            max_reverse_speed: 1.5,
+            ..VehicleConfig::default()
+        };
+        let mut differential = Differential::new(config);
+        let mut state = VehicleState::default();
+
+        // Steering hard enough to run one side backwards
+        for (throttle, steering) in [(0.5, 0.8), (0.2, -0.6), (-0.4, 0.3)] {
+            for _ in 0..500 {
+                differential.step(&mut state, throttle, steering, 1.0, 0.02);
+            }
+
+            let predicted = differential.yaw_rate(state.speed, steering);
+            assert!((predicted - state.yaw_rate).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn stops_against_a_wall() {
+        let mut vehicle = Vehicle::new(VehicleConfig {
+            kind: VehicleKind::Differential,
+            ..VehicleConfig::default()
+        });
+        let world = World {
+            obstacles: vec![Obstacle::Wall {
+                from: [-5.0, 3.0],
+                to: [5.0, 3.0],
+            }],
+            ..World::default()
+        };
+
+        let mut hits = 0;
+        for _ in 0..250 {
+            vehicle.step(1.0, 0.0, 1.0, 0.02);
+
+            if collide_world(&mut vehicle, &world) {
+                hits += 1;
+            }
+        }
+
+        // Still pushing into the wall, but only with what it gains in a tick
+        vehicle.step(1.0, 0.0, 1.0, 0.02);
+        assert!(hits > 0);
+        assert!(vehicle.state.speed < 0.2, "{}", vehicle.state.speed);
+        assert!(vehicle.state.y < 3.0);
+    }
+}
@@ -0,0 +1,203 @@
+pub mod ackermann;
+pub mod boat;
+pub mod differential;
+pub mod quadcopter;
+
+use ackermann::{Ackermann, AckermannModel, DynamicConfig};
+use boat::{Boat, BoatConfig};
+use differential::{Differential, DifferentialConfig};
+use quadcopter::{Quadcopter, QuadcopterConfig};
+
+use common_data::server::data::capabilities::Axis;
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt::Debug;
+
+pub const GRAVITY: f32 = 9.81;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum VehicleKind {
+    Ackermann,
+    Differential,
+    Boat,
+    Quadcopter,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct VehicleConfig {
+    pub kind: VehicleKind,
+    // Distance between the axles in meters
+    pub wheelbase: f32,
+    // Top speed in m/s at full throttle
+    pub max_speed: f32,
+    pub max_reverse_speed: f32,
+    // Steering lock in degrees at full turn
+    pub max_steering_angle: f32,
+    // How fast the vehicle can change speed in m/s^2
+    pub acceleration: f32,
+    // Meters from the middle, vehicles collide as circles this size
+    pub radius: f32,
+    // The rest only apply to their own kind of vehicle
+    pub model: AckermannModel,
+    pub dynamic: DynamicConfig,
+    pub differential: DifferentialConfig,
+    pub boat: BoatConfig,
+    pub quadcopter: QuadcopterConfig,
+}
+
+impl Default for VehicleConfig {
+    fn default() -> Self {
+        return VehicleConfig {
+            kind: VehicleKind::Ackermann,
+            wheelbase: 0.26,
+            max_speed: 8.0,
+            max_reverse_speed: 3.0,
+            max_steering_angle: 30.0,
+            acceleration: 4.0,
+            radius: 0.2,
+            model: AckermannModel::Kinematic,
+            dynamic: DynamicConfig::default(),
+            differential: DifferentialConfig::default(),
+            boat: BoatConfig::default(),
+            quadcopter: QuadcopterConfig::default(),
+        };
+    }
+}
+
+// How a kind of vehicle moves and how it maps the throttle and steering
+// control axes onto its own controls
+pub trait VehicleModel: Debug + Send {
+    // throttle and steering are both -1.0 to 1.0, friction is the grip of the
+    // surface under the vehicle relative to dry tarmac
+    fn step(
+        &mut self,
+        state: &mut VehicleState,
+        throttle: f32,
+        steering: f32,
+        friction: f32,
+        dt: f32,
+    );
+
+    // Radians per second a steering command turns at, ignoring any slip.
+    // Used to dead reckon.
+    fn yaw_rate(&self, speed: f32, steering: f32) -> f32;
+
+    // Steering command that follows a path curving this much, 1 / meters
+    fn steering_for_curvature(&self, curvature: f32, speed: f32) -> f32;
+
+    // Control axes the vehicle reports to drivers
+    fn axes(&self) -> Vec<Axis>;
+
+    // Whether the vehicle can slide sideways, slip angle stays zero otherwise
+    fn slips(&self) -> bool;
+
+    fn clone_box(&self) -> Box<dyn VehicleModel>;
+}
+
+impl Clone for Box<dyn VehicleModel> {
+    fn clone(&self) -> Self {
+        return self.clone_box();
+    }
+}
+
+// Position is meters east (x) and north (y) of the sim origin, heading is
+// radians clockwise from north
+#[derive(Debug, Clone, Default)]
+pub struct VehicleState {
+    pub x: f64,
+    pub y: f64,
+    // Meters above the ground, only quadcopters leave it
+    pub height: f32,
+    pub heading: f32,
+    // m/s along the vehicle
+    pub speed: f32,
+    // m/s across the vehicle, positive to the right
+    pub lateral_speed: f32,
+    // Radians per second, positive clockwise
+    pub yaw_rate: f32,
+    // Radians between where the vehicle points and where it is going
+    pub slip_angle: f32,
+    pub steering_angle: f32,
+    // m/s^2 along and across the vehicle, positive forward and right
+    pub acceleration: [f32; 2],
+    // Biggest change in velocity from a collision since it was last taken,
+    // m/s along and across the vehicle
+    pub impact: [f32; 2],
+}
+
+impl VehicleState {
+    // Moves the position on by the current velocity
+    pub fn advance(&mut self, dt: f32) {
+        let velocity = self.velocity();
+
+        self.x += f64::from(velocity[0] * dt);
+        self.y += f64::from(velocity[1] * dt);
+    }
+
+    // m/s east and north
+    pub fn velocity(&self) -> [f32; 2] {
+        let (sin_heading, cos_heading) = self.heading.sin_cos();
+
+        return [
+            self.speed * sin_heading + self.lateral_speed * cos_heading,
+            self.speed * cos_heading - self.lateral_speed * sin_heading,
+        ];
+    }
+
+    // Splits a velocity east and north into along and across the vehicle
+    pub fn set_velocity(&mut self, velocity: [f32; 2]) {
+        let (sin_heading, cos_heading) = self.heading.sin_cos();
+
+        self.speed = velocity[0] * sin_heading + velocity[1] * cos_heading;
+        self.lateral_speed = velocity[0] * cos_heading - velocity[1] * sin_heading;
+    }
+
+    // Sets a velocity east and north that a collision changed, keeping the
+    // hit if it is the biggest not yet taken
+    pub fn set_velocity_from_impact(&mut self, velocity: [f32; 2]) {
+        let before = [self.speed, self.lateral_speed];
+        self.set_velocity(velocity);
+
+        let change = [self.speed - before[0], self.lateral_speed - before[1]];
+
+        if change[0].hypot(change[1]) > self.impact[0].hypot(self.impact[1]) {
+            self.impact = change;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Vehicle {
+    pub config: VehicleConfig,
+    pub model: Box<dyn VehicleModel>,
+    pub state: VehicleState,
+}
+
+impl Vehicle {
+    pub fn new(config: VehicleConfig) -> Self {
+        let model: Box<dyn VehicleModel> = match config.kind {
+            VehicleKind::Ackermann => Box::new(Ackermann::new(config.clone())),
+            VehicleKind::Differential => Box::new(Differential::new(config.clone())),
+            VehicleKind::Boat => Box::new(Boat::new(config.clone())),
+            VehicleKind::Quadcopter => Box::new(Quadcopter::new(config.clone())),
+        };
+
+        return Vehicle {
+            config,
+            model,
+            state: VehicleState::default(),
+        };
+    }
+
+    pub fn step(&mut self, throttle: f32, steering: f32, friction: f32, dt: f32) {
+        self.model.step(
+            &mut self.state,
+            throttle.clamp(-1.0, 1.0),
+            steering.clamp(-1.0, 1.0),
+            friction.max(0.0),
+            dt,
+        );
+    }
+}
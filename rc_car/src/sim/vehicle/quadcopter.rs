@@ -0,0 +1,207 @@
+use crate::sim::vehicle::{VehicleConfig, VehicleModel, VehicleState};
+
+use common_data::server::data::capabilities::Axis;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct QuadcopterConfig {
+    // Meters above the ground it climbs to and holds once any command comes in
+    pub hover_height: f32,
+    // m/s up and down
+    pub climb_rate: f32,
+    // Radians per second at full yaw
+    pub max_yaw_rate: f32,
+    // Seconds with the sticks centred before it comes down and lands, unset
+    // keeps it hovering until the pilot moves it again
+    pub land_delay: Option<f32>,
+}
+
+impl Default for QuadcopterConfig {
+    fn default() -> Self {
+        return QuadcopterConfig {
+            hover_height: 2.0,
+            climb_rate: 1.0,
+            max_yaw_rate: 2.0,
+            land_delay: None,
+        };
+    }
+}
+
+// Holds its height on its own while commanded. Throttle pitches it forward
+// or back to set the speed along where it points, steering yaws it, centred
+// sticks hover. Turning quickly leaves it sliding sideways until it has
+// turned its velocity round.
+#[derive(Debug, Clone)]
+pub struct Quadcopter {
+    pub config: VehicleConfig,
+    airborne: bool,
+    // Seconds since the last command that was not zero
+    idle: f32,
+}
+
+impl Quadcopter {
+    pub fn new(config: VehicleConfig) -> Self {
+        return Quadcopter {
+            config,
+            airborne: false,
+            idle: 0.0,
+        };
+    }
+}
+
+impl VehicleModel for Quadcopter {
+    // Flying, so surface friction is ignored
+    fn step(
+        &mut self,
+        state: &mut VehicleState,
+        throttle: f32,
+        steering: f32,
+        _friction: f32,
+        dt: f32,
+    ) {
+        if throttle != 0.0 || steering != 0.0 {
+            self.airborne = true;
+            self.idle = 0.0;
+        } else {
+            self.idle += dt;
+        }
+
+        if let Some(land_delay) = self.config.quadcopter.land_delay {
+            if self.idle > land_delay {
+                self.airborne = false;
+            }
+        }
+
+        let target_height = if self.airborne {
+            self.config.quadcopter.hover_height.max(0.0)
+        } else {
+            0.0
+        };
+        let climb = self.config.quadcopter.climb_rate * dt;
+        state.height += (target_height - state.height).clamp(-climb, climb);
+
+        // Nothing moves it sideways on the ground
+        if state.height <= 0.0 {
+            state.speed = 0.0;
+            state.lateral_speed = 0.0;
+            state.yaw_rate = 0.0;
+            state.slip_angle = 0.0;
+            state.acceleration = [0.0, 0.0];
+            return;
+        }
+
+        state.yaw_rate = self.yaw_rate(state.speed, steering);
+
+        let (sin_heading, cos_heading) = state.heading.sin_cos();
+        let velocity = state.velocity();
+
+        let target_speed = if throttle >= 0.0 {
+            throttle * self.config.max_speed
+        } else {
+            throttle * self.config.max_reverse_speed
+        };
+        let target = [target_speed * sin_heading, target_speed * cos_heading];
+
+        // Tilting only gets so much of the thrust sideways
+        let change = [target[0] - velocity[0], target[1] - velocity[1]];
+        let change_size = (change[0].powi(2) + change[1].powi(2)).sqrt();
+        let max_change = self.config.acceleration * dt;
+        let scale = if change_size > max_change {
+            max_change / change_size
+        } else {
+            1.0
+        };
+        let velocity = [
+            velocity[0] + change[0] * scale,
+            velocity[1] + change[1] * scale,
+        ];
+
+        state.heading = (state.heading + state.yaw_rate * dt).rem_euclid(std::f32::consts::TAU);
+
+        let (sin_heading, cos_heading) = state.heading.sin_cos();
+
+        if dt > 0.0 {
+            let east = change[0] * scale / dt;
+            let north = change[1] * scale / dt;
+
+            state.acceleration = [
+                east * sin_heading + north * cos_heading,
+                east * cos_heading - north * sin_heading,
+            ];
+        }
+
+        state.set_velocity(velocity);
+        state.steering_angle = 0.0;
+        state.slip_angle = if velocity[0].abs() + velocity[1].abs() > 0.01 {
+            state.lateral_speed.atan2(state.speed.abs())
+        } else {
+            0.0
+        };
+
+        state.advance(dt);
+    }
+
+    fn yaw_rate(&self, _speed: f32, steering: f32) -> f32 {
+        return steering * self.config.quadcopter.max_yaw_rate;
+    }
+
+    fn steering_for_curvature(&self, curvature: f32, speed: f32) -> f32 {
+        if self.config.quadcopter.max_yaw_rate <= 0.0 {
+            return 0.0;
+        }
+
+        return (curvature * speed / self.config.quadcopter.max_yaw_rate).clamp(-1.0, 1.0);
+    }
+
+    fn axes(&self) -> Vec<Axis> {
+        return vec![Axis::Pitch, Axis::Yaw];
+    }
+
+    fn slips(&self) -> bool {
+        return true;
+    }
+
+    fn clone_box(&self) -> Box<dyn VehicleModel> {
+        return Box::new(self.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fly(quadcopter: &mut Quadcopter, state: &mut VehicleState, throttle: f32, ticks: u32) {
+        for _ in 0..ticks {
+            quadcopter.step(state, throttle, 0.0, 1.0, 0.02);
+        }
+    }
+
+    #[test]
+    fn hovers_with_the_sticks_centred() {
+        let mut quadcopter = Quadcopter::new(VehicleConfig::default());
+        let mut state = VehicleState::default();
+
+        fly(&mut quadcopter, &mut state, 0.5, 250);
+        fly(&mut quadcopter, &mut state, 0.0, 500);
+
+        assert!(state.height > 1.9);
+        assert!(state.speed.abs() < 0.01);
+    }
+
+    #[test]
+    fn lands_after_land_delay_when_set() {
+        let mut config = VehicleConfig::default();
+        config.quadcopter.land_delay = Some(1.0);
+        let mut quadcopter = Quadcopter::new(config);
+        let mut state = VehicleState::default();
+
+        fly(&mut quadcopter, &mut state, 0.5, 250);
+        assert!(state.height > 1.9);
+
+        fly(&mut quadcopter, &mut state, 0.0, 250);
+        assert_eq!(state.height, 0.0);
+        assert_eq!(state.speed, 0.0);
+    }
+}
@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+
+#[derive(Debug, Clone)]
+pub enum WorldError {
+    ReadError,
+    DecodeError,
+}
+
+// Points are meters east and north of the sim origin
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum Obstacle {
+    Circle { center: [f64; 2], radius: f64 },
+    Wall { from: [f64; 2], to: [f64; 2] },
+}
+
+// Edges of the track, each side is a line through its points. Range sensors
+// see them like walls.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Track {
+    pub left: Vec<[f64; 2]>,
+    pub right: Vec<[f64; 2]>,
+    // Joins the last point of each side back to its first, for circuits
+    #[serde(default)]
+    pub closed: bool,
+}
+
+// Area with its own grip, like grass beside the track or a wet patch
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Surface {
+    #[serde(default)]
+    pub name: String,
+    pub polygon: Vec<[f64; 2]>,
+    // Grip relative to dry tarmac at 1.0
+    pub friction: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct StartPosition {
+    pub east: f64,
+    pub north: f64,
+    // Degrees clockwise from north
+    pub heading: f32,
+}
+
+impl Default for StartPosition {
+    fn default() -> Self {
+        return StartPosition {
+            east: 0.0,
+            north: 0.0,
+            heading: 0.0,
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct World {
+    pub name: String,
+    // Latitude and longitude in degrees, replaces the sim origin when set
+    pub gps_origin: Option<[f64; 2]>,
+    pub start: StartPosition,
+    // Where each vehicle starts when several share the world, in order
+    pub grid: Vec<StartPosition>,
+    pub track: Option<Track>,
+    pub obstacles: Vec<Obstacle>,
+    // Later surfaces win where they overlap
+    pub surfaces: Vec<Surface>,
+    // Grip everywhere outside the surfaces
+    pub friction: f32,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        return World {
+            name: String::new(),
+            gps_origin: None,
+            start: StartPosition::default(),
+            grid: Vec::new(),
+            track: None,
+            obstacles: Vec::new(),
+            surfaces: Vec::new(),
+            friction: 1.0,
+        };
+    }
+}
+
+impl World {
+    pub fn load(path: &str) -> Result<World, WorldError> {
+        let file = match fs::read_to_string(path) {
+            Ok(f) => f,
+            Err(_) => return Err(WorldError::ReadError),
+        };
+
+        match serde_json::from_str(&file) {
+            Ok(w) => Ok(w),
+            Err(_) => Err(WorldError::DecodeError),
+        }
+    }
+
+    // Distance along a ray to the nearest obstacle or track edge, heading is
+    // radians clockwise from north. traffic is anything else in the world,
+    // like other vehicles.
+    pub fn ray_cast(
+        &self,
+        origin: [f64; 2],
+        heading: f64,
+        max_range: f64,
+        traffic: &[Obstacle],
+    ) -> Option<f64> {
+        let direction = [heading.sin(), heading.cos()];
+
+        let mut hits: Vec<f64> = Vec::new();
+
+        for obstacle in self.obstacles.iter().chain(traffic.iter()) {
+            let hit = match obstacle {
+                Obstacle::Circle { center, radius } => {
+                    ray_circle(origin, direction, *center, *radius)
+                }
+                Obstacle::Wall { from, to } => ray_segment(origin, direction, *from, *to),
+            };
+
+            hits.extend(hit);
+        }
+
+        for (from, to) in self.track_edges() {
+            hits.extend(ray_segment(origin, direction, from, to));
+        }
+
+        return hits
+            .into_iter()
+            .filter(|d| *d <= max_range)
+            .reduce(f64::min);
+    }
+
+    // Distance from a point to the nearest obstacle or track edge, zero when
+    // inside an obstacle
+    pub fn clearance(&self, point: [f64; 2]) -> Option<f64> {
+        let mut distances: Vec<f64> = Vec::new();
+
+        for obstacle in self.obstacles.iter() {
+            let distance = match obstacle {
+                Obstacle::Circle { center, radius } => (distance(point, *center) - radius).max(0.0),
+                Obstacle::Wall { from, to } => segment_distance(point, *from, *to),
+            };
+
+            distances.push(distance);
+        }
+
+        for (from, to) in self.track_edges() {
+            distances.push(segment_distance(point, from, to));
+        }
+
+        return distances.into_iter().reduce(f64::min);
+    }
+
+    pub fn friction_at(&self, point: [f64; 2]) -> f32 {
+        let mut friction = self.friction;
+
+        for surface in self.surfaces.iter() {
+            if contains(&surface.polygon, point) {
+                friction = surface.friction;
+            }
+        }
+
+        return friction.max(0.0);
+    }
+
+    pub fn track_edges(&self) -> Vec<([f64; 2], [f64; 2])> {
+        let mut edges = Vec::new();
+
+        let track = match &self.track {
+            Some(t) => t,
+            None => return edges,
+        };
+
+        for side in [&track.left, &track.right] {
+            for pair in side.windows(2) {
+                edges.push((pair[0], pair[1]));
+            }
+
+            if track.closed && side.len() > 2 {
+                edges.push((side[side.len() - 1], side[0]));
+            }
+        }
+
+        return edges;
+    }
+}
+
+fn ray_circle(origin: [f64; 2], direction: [f64; 2], center: [f64; 2], radius: f64) -> Option<f64> {
+    let offset = [origin[0] - center[0], origin[1] - center[1]];
+
+    let b = offset[0] * direction[0] + offset[1] * direction[1];
+    let c = offset[0].powi(2) + offset[1].powi(2) - radius.powi(2);
+
+    // Starting inside the circle counts as touching it
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+
+    let discriminant = b * b - c;
+
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let distance = -b - discriminant.sqrt();
+
+    if distance < 0.0 {
+        return None;
+    }
+
+    return Some(distance);
+}
+
+fn ray_segment(origin: [f64; 2], direction: [f64; 2], from: [f64; 2], to: [f64; 2]) -> Option<f64> {
+    let edge = [to[0] - from[0], to[1] - from[1]];
+    let denominator = direction[0] * edge[1] - direction[1] * edge[0];
+
+    // Parallel to the wall
+    if denominator.abs() < 1e-12 {
+        return None;
+    }
+
+    let offset = [from[0] - origin[0], from[1] - origin[1]];
+    let distance = (offset[0] * edge[1] - offset[1] * edge[0]) / denominator;
+    let along = (offset[0] * direction[1] - offset[1] * direction[0]) / denominator;
+
+    if distance < 0.0 || !(0.0..=1.0).contains(&along) {
+        return None;
+    }
+
+    return Some(distance);
+}
+
+fn distance(from: [f64; 2], to: [f64; 2]) -> f64 {
+    return ((to[0] - from[0]).powi(2) + (to[1] - from[1]).powi(2)).sqrt();
+}
+
+fn segment_distance(point: [f64; 2], from: [f64; 2], to: [f64; 2]) -> f64 {
+    return distance(point, nearest_on_segment(point, from, to));
+}
+
+pub fn nearest_on_segment(point: [f64; 2], from: [f64; 2], to: [f64; 2]) -> [f64; 2] {
+    let edge = [to[0] - from[0], to[1] - from[1]];
+    let length_squared = edge[0].powi(2) + edge[1].powi(2);
+
+    if length_squared <= 0.0 {
+        return from;
+    }
+
+    let along = (((point[0] - from[0]) * edge[0] + (point[1] - from[1]) * edge[1])
+        / length_squared)
+        .clamp(0.0, 1.0);
+
+    return [from[0] + edge[0] * along, from[1] + edge[1] * along];
+}
+
+// Even-odd rule point in polygon test
+fn contains(polygon: &[[f64; 2]], point: [f64; 2]) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+
+    for current in polygon.iter() {
+        if (current[1] > point[1]) != (previous[1] > point[1]) {
+            let crossing = current[0]
+                + (point[1] - current[1]) / (previous[1] - current[1]) * (previous[0] - current[0]);
+
+            if point[0] < crossing {
+                inside = !inside;
+            }
+        }
+
+        previous = *current;
+    }
+
+    return inside;
+}
@@ -0,0 +1,51 @@
+use crate::cueing::MotionCueConfig;
+use crate::dashboard::DashboardConfig;
+use crate::feedback::FeedbackConfig;
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+
+#[derive(Debug, Clone)]
+pub enum ConfigError {
+    ReadError,
+    DecodeError,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RigConfig {
+    // Driver software sends control packets here as if the rig were the car
+    pub listen_address: String,
+    // Control address of the car being driven
+    pub car_address: String,
+    pub motion: MotionCueConfig,
+    pub feedback: FeedbackConfig,
+    pub dashboard: DashboardConfig,
+}
+
+impl Default for RigConfig {
+    fn default() -> Self {
+        return RigConfig {
+            listen_address: "127.0.0.1:6000".to_string(),
+            car_address: "127.0.0.1:5000".to_string(),
+            motion: MotionCueConfig::default(),
+            feedback: FeedbackConfig::default(),
+            dashboard: DashboardConfig::default(),
+        };
+    }
+}
+
+impl RigConfig {
+    pub fn load(path: &str) -> Result<RigConfig, ConfigError> {
+        let file = match fs::read_to_string(path) {
+            Ok(f) => f,
+            Err(_) => return Err(ConfigError::ReadError),
+        };
+
+        match serde_json::from_str(&file) {
+            Ok(c) => Ok(c),
+            Err(_) => Err(ConfigError::DecodeError),
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+
+use std::f32::consts::PI;
+
+const GRAVITY: f32 = 9.81;
+
+pub const CUE_FORMAT_VERSION: u8 = 1;
+
+pub const CUE_PACKET_SIZE: usize = 25;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MotionCueConfig {
+    // UDP address the seat listens on for cue packets, unset sends none
+    pub address: Option<String>,
+    // Cue packets a second
+    pub rate: u32,
+    // Seconds without motion from the car before the seat settles back
+    pub timeout: f32,
+    // Fraction of the car's acceleration the seat reproduces
+    pub scale: f32,
+    // Hz, quicker changes in acceleration move the seat, slower ones tilt it
+    pub translation_cutoff: f32,
+    pub translation_damping: f32,
+    // Hz the seat drifts back to the middle of its travel at
+    pub return_cutoff: f32,
+    // Meters each way from the middle
+    pub max_travel: f32,
+    // Hz, acceleration held longer than this is felt by tilting the seat
+    pub tilt_cutoff: f32,
+    // Degrees per second, slow enough the driver feels the lean and not the
+    // rotation
+    pub max_tilt_rate: f32,
+    // Degrees
+    pub max_tilt: f32,
+}
+
+impl Default for MotionCueConfig {
+    fn default() -> Self {
+        return MotionCueConfig {
+            address: None,
+            rate: 100,
+            timeout: 0.5,
+            scale: 0.15,
+            translation_cutoff: 0.4,
+            translation_damping: 1.0,
+            return_cutoff: 0.1,
+            max_travel: 0.1,
+            tilt_cutoff: 0.5,
+            max_tilt_rate: 3.0,
+            max_tilt: 15.0,
+        };
+    }
+}
+
+// Where the seat should be. Cue packets sent to the seat are 25 bytes with
+// every value big endian:
+//
+//   0       u8   format version, 1
+//   1..5    u32  sequence, one more each packet
+//   5..9    f32  surge, meters forward of the middle
+//   9..13   f32  sway, meters right of the middle
+//   13..17  f32  heave, meters up from the middle
+//   17..21  f32  roll, radians right side down
+//   21..25  f32  pitch, radians nose up
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MotionCue {
+    pub surge: f32,
+    pub sway: f32,
+    pub heave: f32,
+    pub roll: f32,
+    pub pitch: f32,
+}
+
+impl MotionCue {
+    pub fn generate_packet(&self, sequence: u32) -> [u8; CUE_PACKET_SIZE] {
+        let mut packet = [0u8; CUE_PACKET_SIZE];
+
+        packet[0] = CUE_FORMAT_VERSION;
+        packet[1..5].copy_from_slice(&sequence.to_be_bytes());
+
+        let values = [self.surge, self.sway, self.heave, self.roll, self.pitch];
+
+        for (index, value) in values.iter().enumerate() {
+            let at = 5 + index * 4;
+            packet[at..at + 4].copy_from_slice(&value.to_be_bytes());
+        }
+
+        return packet;
+    }
+}
+
+// One translational axis of the washout. The onset of an acceleration moves
+// the seat, then a high pass lets it drift back before it runs out of travel.
+#[derive(Debug, Clone, Default)]
+struct TranslationChannel {
+    last_input: f32,
+    high_passed: f32,
+    velocity: f32,
+    position: f32,
+}
+
+impl TranslationChannel {
+    fn update(&mut self, config: &MotionCueConfig, acceleration: f32, dt: f32) -> f32 {
+        // Sustained acceleration washes out
+        let return_tau = 1.0 / (2.0 * PI * config.return_cutoff.max(0.001));
+        let alpha = return_tau / (return_tau + dt);
+        self.high_passed = alpha * (self.high_passed + acceleration - self.last_input);
+        self.last_input = acceleration;
+
+        // Second order high pass into position, a damped spring driven by
+        // the acceleration
+        let omega = 2.0 * PI * config.translation_cutoff.max(0.001);
+        let spring = self.high_passed
+            - 2.0 * config.translation_damping * omega * self.velocity
+            - omega.powi(2) * self.position;
+
+        self.velocity += spring * dt;
+        self.position += self.velocity * dt;
+
+        if self.position.abs() > config.max_travel {
+            self.position = self.position.clamp(-config.max_travel, config.max_travel);
+            self.velocity = 0.0;
+        }
+
+        return self.position;
+    }
+}
+
+// Tilts the seat so gravity stands in for a sustained acceleration
+#[derive(Debug, Clone, Default)]
+struct TiltChannel {
+    low_passed: f32,
+    angle: f32,
+}
+
+impl TiltChannel {
+    fn update(&mut self, config: &MotionCueConfig, acceleration: f32, dt: f32) -> f32 {
+        let tau = 1.0 / (2.0 * PI * config.tilt_cutoff.max(0.001));
+        self.low_passed += (acceleration - self.low_passed) * dt / (tau + dt);
+
+        let max_tilt = config.max_tilt.to_radians();
+        let target = (self.low_passed / GRAVITY)
+            .clamp(-1.0, 1.0)
+            .asin()
+            .clamp(-max_tilt, max_tilt);
+
+        let max_change = config.max_tilt_rate.to_radians() * dt;
+        self.angle += (target - self.angle).clamp(-max_change, max_change);
+
+        return self.angle;
+    }
+}
+
+// Classical washout filter, turns the car's acceleration into seat movement
+// that fits in the seat's travel
+#[derive(Debug, Clone)]
+pub struct Washout {
+    pub config: MotionCueConfig,
+    surge: TranslationChannel,
+    sway: TranslationChannel,
+    heave: TranslationChannel,
+    pitch: TiltChannel,
+    roll: TiltChannel,
+}
+
+impl Washout {
+    pub fn new(config: MotionCueConfig) -> Self {
+        return Washout {
+            config,
+            surge: TranslationChannel::default(),
+            sway: TranslationChannel::default(),
+            heave: TranslationChannel::default(),
+            pitch: TiltChannel::default(),
+            roll: TiltChannel::default(),
+        };
+    }
+
+    // Acceleration is m/s^2 forward, right and up including gravity, as the
+    // car's accelerometer reports it. None settles the seat back level.
+    pub fn update(&mut self, acceleration: Option<[f32; 3]>, dt: f32) -> MotionCue {
+        let acceleration = acceleration.unwrap_or([0.0, 0.0, GRAVITY]);
+        let scale = self.config.scale;
+
+        let forward = acceleration[0] * scale;
+        let right = acceleration[1] * scale;
+        let up = (acceleration[2] - GRAVITY) * scale;
+
+        // Pushed back in the seat when speeding up, so lean back. Pushed
+        // left in a right hand turn, so lean left.
+        return MotionCue {
+            surge: self.surge.update(&self.config, forward, dt),
+            sway: self.sway.update(&self.config, right, dt),
+            heave: self.heave.update(&self.config, up, dt),
+            pitch: self.pitch.update(&self.config, forward, dt),
+            roll: -self.roll.update(&self.config, right, dt),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 0.01;
+
+    fn run(washout: &mut Washout, acceleration: [f32; 3], seconds: f32) -> Vec<MotionCue> {
+        let steps = (seconds / DT).round() as usize;
+
+        return (0..steps)
+            .map(|_| washout.update(Some(acceleration), DT))
+            .collect();
+    }
+
+    #[test]
+    fn surge_from_a_step_washes_back_to_neutral() {
+        let mut washout = Washout::new(MotionCueConfig::default());
+
+        let onset = run(&mut washout, [3.0, 0.0, GRAVITY], 1.0);
+        let peak = onset.iter().map(|c| c.surge).fold(0.0, f32::max);
+        assert!(peak > 0.01);
+
+        let held = run(&mut washout, [3.0, 0.0, GRAVITY], 30.0);
+        assert!(held[held.len() - 1].surge.abs() < 0.002);
+    }
+
+    #[test]
+    fn sustained_acceleration_becomes_tilt() {
+        let mut washout = Washout::new(MotionCueConfig::default());
+
+        let cues = run(&mut washout, [3.0, 2.0, GRAVITY], 20.0);
+        let last = cues[cues.len() - 1];
+
+        // Lean back to feel pushed into the seat, lean left in a right turn
+        let scale = washout.config.scale;
+        assert!((last.pitch - (3.0 * scale / GRAVITY).asin()).abs() < 0.002);
+        assert!((last.roll + (2.0 * scale / GRAVITY).asin()).abs() < 0.002);
+    }
+
+    #[test]
+    fn settles_level_without_motion() {
+        let mut washout = Washout::new(MotionCueConfig::default());
+        run(&mut washout, [3.0, 2.0, GRAVITY], 5.0);
+
+        let mut cue = MotionCue::default();
+        for _ in 0..3000 {
+            cue = washout.update(None, DT);
+        }
+
+        assert!(cue.pitch.abs() < 0.002 && cue.roll.abs() < 0.002);
+        assert!(cue.surge.abs() < 0.002 && cue.sway.abs() < 0.002);
+    }
+}
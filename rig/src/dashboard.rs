@@ -0,0 +1,134 @@
+use common_data::commands::status::Status;
+
+use serde::{Deserialize, Serialize};
+
+pub const DASHBOARD_FORMAT_VERSION: u8 = 1;
+
+pub const DASHBOARD_PACKET_SIZE: usize = 31;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DashboardConfig {
+    // UDP address dashboard displays listen on, can be a broadcast address.
+    // Unset sends none.
+    pub address: Option<String>,
+    // Dashboard packets a second
+    pub rate: u32,
+    // Seconds without status from the car before the dashboard is told the
+    // car stopped reporting
+    pub timeout: f32,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        return DashboardConfig {
+            address: None,
+            rate: 20,
+            timeout: 2.0,
+        };
+    }
+}
+
+// Dashboard packets are 31 bytes with every value big endian. Later versions
+// only add bytes on the end.
+//
+//   0       u8   format version, 1
+//   1..5    u32  sequence, one more each packet
+//   5       u8   flags, bit 0 set while the car is reporting. Without it the
+//                rest is the last the car sent, or zeros if it never has.
+//   6..10   f32  speed, m/s, negative in reverse
+//   10      u8   battery charge, percent
+//   11..15  f32  heading, degrees clockwise from north
+//   15..17  u16  round trip latency to the car, ms, 0 when not measured
+//   17..19  u16  laps completed
+//   19..23  f32  current lap time, s, 0 before the first crossing of the line
+//   23..27  f32  last lap time, s, 0 before the first lap
+//   27..31  f32  best lap time, s, 0 before the first lap
+pub fn generate_packet(
+    status: Option<&Status>,
+    reporting: bool,
+    sequence: u32,
+) -> [u8; DASHBOARD_PACKET_SIZE] {
+    let mut packet = [0u8; DASHBOARD_PACKET_SIZE];
+
+    packet[0] = DASHBOARD_FORMAT_VERSION;
+    packet[1..5].copy_from_slice(&sequence.to_be_bytes());
+
+    let status = match status {
+        Some(s) => s,
+        None => return packet,
+    };
+
+    packet[5] = u8::from(reporting);
+    packet[6..10].copy_from_slice(&status.speed.to_be_bytes());
+    packet[10] = status.battery_charge;
+    packet[11..15].copy_from_slice(&status.heading.to_be_bytes());
+    packet[15..17].copy_from_slice(&status.latency.to_be_bytes());
+    packet[17..19].copy_from_slice(&status.laps.to_be_bytes());
+    packet[19..23].copy_from_slice(&status.lap_time.to_be_bytes());
+    packet[23..27].copy_from_slice(&status.last_lap.to_be_bytes());
+    packet[27..31].copy_from_slice(&status.best_lap.to_be_bytes());
+
+    return packet;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> Status {
+        return Status {
+            sequence: 9,
+            speed: -1.5,
+            battery_charge: 87,
+            heading: 271.25,
+            latency: 0x0123,
+            laps: 0x0405,
+            lap_time: 12.5,
+            last_lap: 31.75,
+            best_lap: 30.125,
+            ..Status::default()
+        };
+    }
+
+    fn float(packet: &[u8], at: usize) -> f32 {
+        return f32::from_be_bytes([packet[at], packet[at + 1], packet[at + 2], packet[at + 3]]);
+    }
+
+    #[test]
+    fn packet_layout() {
+        let packet = generate_packet(Some(&status()), true, 0x0a0b0c0d);
+
+        assert_eq!(packet.len(), 31);
+        assert_eq!(packet[0], DASHBOARD_FORMAT_VERSION);
+        assert_eq!(packet[1..5], [0x0a, 0x0b, 0x0c, 0x0d]);
+        assert_eq!(packet[5], 1);
+        assert_eq!(float(&packet, 6), -1.5);
+        assert_eq!(packet[10], 87);
+        assert_eq!(float(&packet, 11), 271.25);
+        assert_eq!(packet[15..17], [0x01, 0x23]);
+        assert_eq!(packet[17..19], [0x04, 0x05]);
+        assert_eq!(float(&packet, 19), 12.5);
+        assert_eq!(float(&packet, 23), 31.75);
+        assert_eq!(float(&packet, 27), 30.125);
+    }
+
+    #[test]
+    fn keeps_the_last_status_when_not_reporting() {
+        let reporting = generate_packet(Some(&status()), true, 3);
+        let stale = generate_packet(Some(&status()), false, 3);
+
+        assert_eq!(stale[5], 0);
+        assert_eq!(stale[..5], reporting[..5]);
+        assert_eq!(stale[6..], reporting[6..]);
+    }
+
+    #[test]
+    fn zeros_before_any_status() {
+        let packet = generate_packet(None, true, 7);
+
+        assert_eq!(packet[0], DASHBOARD_FORMAT_VERSION);
+        assert_eq!(packet[1..5], 7u32.to_be_bytes());
+        assert!(packet[5..].iter().all(|b| *b == 0));
+    }
+}
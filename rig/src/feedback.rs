@@ -0,0 +1,241 @@
+use common_data::commands::motion::Motion;
+
+use serde::{Deserialize, Serialize};
+
+pub const TORQUE_FORMAT_VERSION: u8 = 1;
+
+pub const TORQUE_PACKET_SIZE: usize = 9;
+
+// Torques are fractions of the strongest the wheel can push, positive turns
+// the wheel clockwise
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FeedbackConfig {
+    // UDP address the wheel driver listens on for torque packets, unset
+    // sends none
+    pub address: Option<String>,
+    // Torque packets a second
+    pub rate: u32,
+    // Seconds without motion from the car before the wheel goes limp
+    pub timeout: f32,
+    // Torque per m/s^2 of cornering, the front tires pulling themselves
+    // straight
+    pub lateral_gain: f32,
+    // m/s^2 of cornering where the front tires give up and the pull has
+    // faded away, so the wheel goes light at the limit
+    pub grip: f32,
+    // Torque per radian of steering once up to centering_speed m/s, caster
+    // pulling the wheels straight
+    pub centering_gain: f32,
+    pub centering_speed: f32,
+    // Torque per radian per second of steering, keeps the wheel from
+    // oscillating
+    pub damping: f32,
+    // Torque per m/s of change in velocity when the car hits something
+    pub impact_gain: f32,
+    // Seconds a kick from a hit takes to die away
+    pub impact_decay: f32,
+}
+
+impl Default for FeedbackConfig {
+    fn default() -> Self {
+        return FeedbackConfig {
+            address: None,
+            rate: 200,
+            timeout: 0.5,
+            lateral_gain: 0.06,
+            grip: 12.0,
+            centering_gain: 0.8,
+            centering_speed: 3.0,
+            damping: 0.01,
+            impact_gain: 0.2,
+            impact_decay: 0.2,
+        };
+    }
+}
+
+// Torque packets sent to the wheel driver are 9 bytes with every value big
+// endian:
+//
+//   0       u8   format version, 1
+//   1..5    u32  sequence, one more each packet
+//   5..9    f32  torque, -1.0 to 1.0, positive turns the wheel clockwise
+pub fn generate_packet(torque: f32, sequence: u32) -> [u8; TORQUE_PACKET_SIZE] {
+    let mut packet = [0u8; TORQUE_PACKET_SIZE];
+
+    packet[0] = TORQUE_FORMAT_VERSION;
+    packet[1..5].copy_from_slice(&sequence.to_be_bytes());
+    packet[5..9].copy_from_slice(&torque.to_be_bytes());
+
+    return packet;
+}
+
+// Self aligning torque for the rig's steering wheel, worked out from how the
+// remote car is moving
+#[derive(Debug, Clone)]
+pub struct ForceFeedback {
+    pub config: FeedbackConfig,
+    pub torque: f32,
+    // Torque left over from hits, dying away
+    kick: f32,
+    // Sequence and steering angle of the last motion used, and the seconds
+    // since it came
+    last_motion: Option<(u16, f32)>,
+    since_motion: f32,
+    steering_rate: f32,
+}
+
+impl ForceFeedback {
+    pub fn new(config: FeedbackConfig) -> Self {
+        return ForceFeedback {
+            config,
+            torque: 0.0,
+            kick: 0.0,
+            last_motion: None,
+            since_motion: 0.0,
+            steering_rate: 0.0,
+        };
+    }
+
+    // Motion is the latest from the car, the same one can be passed many
+    // times. None lets the wheel go limp.
+    pub fn update(&mut self, motion: Option<&Motion>, dt: f32) -> f32 {
+        let config = &self.config;
+
+        self.kick *= (-dt / config.impact_decay.max(0.001)).exp();
+        self.since_motion += dt;
+
+        let motion = match motion {
+            Some(m) => m,
+            None => {
+                self.last_motion = None;
+                self.steering_rate = 0.0;
+                self.torque = 0.0;
+                return self.torque;
+            }
+        };
+
+        // Hits and steering rate only count once for each packet
+        if self.last_motion.map(|(s, _)| s) != Some(motion.sequence) {
+            if let Some((_, last_steering)) = self.last_motion {
+                if self.since_motion > 0.0 {
+                    self.steering_rate =
+                        (motion.steering_angle - last_steering) / self.since_motion;
+                }
+            }
+
+            // A hit from the side knocks the front wheels the way it pushes
+            // the car, one from the front snaps them further onto their lock
+            let lock = if motion.steering_angle.abs() > 0.01 {
+                motion.steering_angle.signum()
+            } else {
+                0.0
+            };
+            self.kick += motion.impact[1] * config.impact_gain;
+            self.kick += motion.impact[0].min(0.0).abs() * config.impact_gain * lock;
+
+            self.last_motion = Some((motion.sequence, motion.steering_angle));
+            self.since_motion = 0.0;
+        }
+
+        let lateral = motion.acceleration[1];
+        let trail = (1.0 - lateral.abs() / config.grip.max(0.001)).max(0.0);
+        let aligning = -lateral * config.lateral_gain * trail;
+
+        let caster = (motion.speed.abs() / config.centering_speed.max(0.001)).min(1.0);
+        let centering = -motion.steering_angle * config.centering_gain * caster;
+
+        let damping = -self.steering_rate * config.damping;
+
+        self.torque = (aligning + centering + damping + self.kick).clamp(-1.0, 1.0);
+
+        return self.torque;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 0.005;
+
+    fn motion(sequence: u16, speed: f32, lateral: f32, steering_angle: f32) -> Motion {
+        return Motion::new(
+            sequence,
+            speed,
+            [0.0, lateral, 9.81],
+            0.0,
+            steering_angle,
+            [0.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn aligning_torque_pulls_the_wheel_straight() {
+        let config = FeedbackConfig::default();
+        let torque = |speed: f32, lateral: f32, steering: f32| -> f32 {
+            let mut feedback = ForceFeedback::new(config.clone());
+            return feedback.update(Some(&motion(1, speed, lateral, steering)), DT);
+        };
+
+        // Cornering right at speed pulls the wheel back anticlockwise
+        let right = torque(5.0, 3.0, 0.1);
+        let expected =
+            -3.0 * config.lateral_gain * (1.0 - 3.0 / config.grip) - 0.1 * config.centering_gain;
+        assert!((right - expected).abs() < 1e-6);
+        assert!((torque(5.0, -3.0, -0.1) + right).abs() < 1e-6);
+
+        // More steering pulls harder, caster only builds up with speed
+        assert!(torque(5.0, 0.0, 0.2) < torque(5.0, 0.0, 0.1));
+        assert!((torque(1.5, 0.0, 0.1) - torque(5.0, 0.0, 0.1) / 2.0).abs() < 1e-6);
+
+        // Past the grip the front tires let go and the wheel goes light
+        assert!(torque(5.0, config.grip, 0.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn no_torque_without_motion() {
+        let mut feedback = ForceFeedback::new(FeedbackConfig::default());
+
+        assert_eq!(feedback.update(Some(&motion(1, 0.0, 0.0, 0.0)), DT), 0.0);
+
+        feedback.update(Some(&motion(2, 5.0, 3.0, 0.1)), DT);
+        assert_eq!(feedback.update(None, DT), 0.0);
+        assert_eq!(feedback.torque, 0.0);
+    }
+
+    #[test]
+    fn collision_kick_dies_away() {
+        let config = FeedbackConfig::default();
+        let mut feedback = ForceFeedback::new(config.clone());
+
+        let mut hit = motion(1, 0.0, 0.0, 0.0);
+        hit.impact = [0.0, 2.0];
+
+        let kick = feedback.update(Some(&hit), DT);
+        assert!((kick - 2.0 * config.impact_gain).abs() < 1e-6);
+
+        // The same packet again does not kick a second time
+        let steps = (config.impact_decay / DT).round() as usize;
+        let mut torque = kick;
+        for _ in 0..steps {
+            torque = feedback.update(Some(&hit), DT);
+        }
+        assert!((torque - kick * (-1.0f32).exp()).abs() < 1e-3);
+
+        for _ in 0..steps * 10 {
+            torque = feedback.update(Some(&hit), DT);
+        }
+        assert!(torque.abs() < 1e-4);
+    }
+
+    #[test]
+    fn torque_packet_layout() {
+        let packet = generate_packet(-0.25, 0x01020304);
+
+        assert_eq!(packet.len(), 9);
+        assert_eq!(packet[0], TORQUE_FORMAT_VERSION);
+        assert_eq!(packet[1..5], [1, 2, 3, 4]);
+        assert_eq!(packet[5..9], (-0.25f32).to_be_bytes());
+    }
+}
@@ -0,0 +1,332 @@
+mod config;
+mod cueing;
+mod dashboard;
+mod feedback;
+
+use config::RigConfig;
+use cueing::Washout;
+use feedback::ForceFeedback;
+
+use common_data::commands::motion::{self, Motion};
+use common_data::commands::status::{self, Status};
+
+use tokio::net::UdpSocket;
+use tokio::time::{interval, Duration, Instant};
+
+use std::env;
+use std::net::SocketAddr;
+
+// Most packets from the car can arrive out of order before a jump back is
+// taken for the car restarting
+const REORDER_WINDOW: i16 = 100;
+
+// Sits between the driver's software and the car. Control packets go through
+// untouched, what the car streams back drives the rig's seat, wheel and
+// dashboard.
+#[tokio::main]
+async fn main() {
+    let config_path = match env::var("RIG_CONFIG") {
+        Err(_) => "rig.json".to_string(),
+        Ok(v) => v,
+    };
+
+    let config = match RigConfig::load(&config_path) {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Warning: Rig config not loaded, using defaults");
+            RigConfig::default()
+        }
+    };
+
+    relay(config).await;
+}
+
+// Runs the relay until the process is stopped
+async fn relay(config: RigConfig) {
+    let driver_socket = UdpSocket::bind(&config.listen_address)
+        .await
+        .expect("cannot bind to listen address");
+
+    let car_socket = UdpSocket::bind("0.0.0.0:0")
+        .await
+        .expect("cannot bind car socket");
+    car_socket
+        .connect(&config.car_address)
+        .await
+        .expect("cannot reach car address");
+
+    let cue_socket = output_socket(config.motion.address.as_ref()).await;
+    let feedback_socket = output_socket(config.feedback.address.as_ref()).await;
+
+    let cue_step = Duration::from_secs_f64(1.0 / f64::from(config.motion.rate.max(1)));
+    let mut cue_tick = interval(cue_step);
+    let mut washout = Washout::new(config.motion.clone());
+    let mut cue_sequence: u32 = 0;
+    let mut last_cue = Instant::now();
+
+    let feedback_step = Duration::from_secs_f64(1.0 / f64::from(config.feedback.rate.max(1)));
+    let mut feedback_tick = interval(feedback_step);
+    let mut force_feedback = ForceFeedback::new(config.feedback.clone());
+    let mut feedback_sequence: u32 = 0;
+    let mut last_feedback = Instant::now();
+
+    let dashboard_socket = output_socket(config.dashboard.address.as_ref()).await;
+    let mut dashboard_tick = interval(Duration::from_secs_f64(
+        1.0 / f64::from(config.dashboard.rate.max(1)),
+    ));
+    let mut dashboard_sequence: u32 = 0;
+
+    // Latest motion from the car and when it came
+    let mut latest: Option<(Motion, Instant)> = None;
+    let mut latest_status: Option<(Status, Instant)> = None;
+    // Where the driver's packets come from, the car's replies go back there
+    let mut driver: Option<SocketAddr> = None;
+
+    let mut driver_buffer = [0u8; 64];
+    let mut car_buffer = [0u8; 64];
+
+    loop {
+        tokio::select! {
+            received = driver_socket.recv_from(&mut driver_buffer) => {
+                let (size, address) = match received {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                };
+
+                driver = Some(address);
+                let _ = car_socket.send(&driver_buffer[..size]).await;
+            }
+            received = car_socket.recv(&mut car_buffer) => {
+                let size = match received {
+                    Ok(s) => s,
+                    Err(_) => continue,
+                };
+
+                match (car_buffer.first(), size) {
+                    (Some(&motion::COMMAND_NUMBER), motion::PACKET_SIZE) => {
+                        let mut packet = [0u8; motion::PACKET_SIZE];
+                        packet.copy_from_slice(&car_buffer[..size]);
+
+                        let decoded = match Motion::decode_packet(packet) {
+                            Ok(m) => m,
+                            Err(_) => continue,
+                        };
+
+                        let held = latest.as_ref().map(|(m, at)| (m.sequence, *at));
+
+                        if replaces(decoded.sequence, held, config.motion.timeout, Instant::now()) {
+                            latest = Some((decoded, Instant::now()));
+                        }
+                    }
+                    (Some(&status::COMMAND_NUMBER), status::PACKET_SIZE) => {
+                        let mut packet = [0u8; status::PACKET_SIZE];
+                        packet.copy_from_slice(&car_buffer[..size]);
+
+                        let decoded = match Status::decode_packet(packet) {
+                            Ok(s) => s,
+                            Err(_) => continue,
+                        };
+
+                        let held = latest_status.as_ref().map(|(s, at)| (s.sequence, *at));
+
+                        if replaces(decoded.sequence, held, config.dashboard.timeout, Instant::now()) {
+                            latest_status = Some((decoded, Instant::now()));
+                        }
+                    }
+                    _ => {
+                        if let Some(address) = driver {
+                            let _ = driver_socket.send_to(&car_buffer[..size], address).await;
+                        }
+                    }
+                };
+            }
+            _ = cue_tick.tick(), if cue_socket.is_some() => {
+                let now = Instant::now();
+                // A stalled loop must not kick the seat
+                let dt = (now - last_cue).min(cue_step * 5).as_secs_f32();
+                last_cue = now;
+
+                let acceleration = fresh(&latest, config.motion.timeout, now).map(|m| m.acceleration);
+                let cue = washout.update(acceleration, dt);
+
+                if let Some(socket) = cue_socket.as_ref() {
+                    let _ = socket.send(&cue.generate_packet(cue_sequence)).await;
+                }
+                cue_sequence = cue_sequence.wrapping_add(1);
+            }
+            _ = feedback_tick.tick(), if feedback_socket.is_some() => {
+                let now = Instant::now();
+                let dt = (now - last_feedback).min(feedback_step * 5).as_secs_f32();
+                last_feedback = now;
+
+                let torque = force_feedback.update(fresh(&latest, config.feedback.timeout, now), dt);
+
+                if let Some(socket) = feedback_socket.as_ref() {
+                    let _ = socket.send(&feedback::generate_packet(torque, feedback_sequence)).await;
+                }
+                feedback_sequence = feedback_sequence.wrapping_add(1);
+            }
+            _ = dashboard_tick.tick(), if dashboard_socket.is_some() => {
+                let timeout = Duration::from_secs_f32(config.dashboard.timeout.max(0.0));
+                let reporting = matches!(latest_status.as_ref(), Some((_, at)) if at.elapsed() <= timeout);
+                let packet = dashboard::generate_packet(
+                    latest_status.as_ref().map(|(s, _)| s),
+                    reporting,
+                    dashboard_sequence,
+                );
+
+                if let Some(socket) = dashboard_socket.as_ref() {
+                    let _ = socket.send(&packet).await;
+                }
+                dashboard_sequence = dashboard_sequence.wrapping_add(1);
+            }
+        }
+    }
+}
+
+// Whether a packet from the car replaces the one held. Anything older is
+// thrown away, unless the held one has gone stale or the count jumped back
+// further than packets get reordered, which is the car restarting.
+fn replaces(sequence: u16, held: Option<(u16, Instant)>, timeout: f32, now: Instant) -> bool {
+    let (held_sequence, at) = match held {
+        Some(h) => h,
+        None => return true,
+    };
+
+    if now - at > Duration::from_secs_f32(timeout.max(0.0)) {
+        return true;
+    }
+
+    let ahead = sequence.wrapping_sub(held_sequence) as i16;
+
+    return !(-REORDER_WINDOW..=0).contains(&ahead);
+}
+
+// Socket for one of the rig's outputs, None when it has no address
+async fn output_socket(address: Option<&String>) -> Option<UdpSocket> {
+    let address = address?;
+
+    let socket = UdpSocket::bind("0.0.0.0:0")
+        .await
+        .expect("cannot bind output socket");
+    // Lets a dashboard go to every display on the network
+    socket
+        .set_broadcast(true)
+        .expect("cannot allow broadcast on output socket");
+    socket
+        .connect(address)
+        .await
+        .expect("cannot reach output address");
+
+    return Some(socket);
+}
+
+// The latest motion from the car, unless it stopped sending more than
+// timeout seconds ago
+fn fresh(latest: &Option<(Motion, Instant)>, timeout: f32, now: Instant) -> Option<&Motion> {
+    match latest {
+        Some((m, at)) if now - *at <= Duration::from_secs_f32(timeout.max(0.0)) => Some(m),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cueing::{MotionCueConfig, CUE_PACKET_SIZE};
+
+    #[test]
+    fn newer_packets_replace_older_ones() {
+        let now = Instant::now();
+
+        assert!(replaces(5, None, 0.5, now));
+        assert!(replaces(6, Some((5, now)), 0.5, now));
+        assert!(replaces(2, Some((u16::MAX - 1, now)), 0.5, now));
+        assert!(!replaces(4, Some((5, now)), 0.5, now));
+    }
+
+    #[test]
+    fn restarted_car_is_taken_up() {
+        let now = Instant::now();
+
+        // The count jumping back a long way
+        assert!(replaces(0, Some((20000, now)), 0.5, now));
+
+        // A late packet once the held one has gone stale
+        let stale = now - Duration::from_secs(1);
+        assert!(replaces(4, Some((5, stale)), 0.5, now));
+    }
+
+    #[tokio::test]
+    async fn motion_from_the_car_comes_out_as_cues() {
+        let car = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let seat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let driver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+        let listen_address = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let config = RigConfig {
+            listen_address: listen_address.to_string(),
+            car_address: car.local_addr().unwrap().to_string(),
+            // Long enough the restart can only be taken up from the count
+            motion: MotionCueConfig {
+                address: Some(seat.local_addr().unwrap().to_string()),
+                timeout: 5.0,
+                ..MotionCueConfig::default()
+            },
+            ..RigConfig::default()
+        };
+        tokio::spawn(relay(config));
+
+        // The car learns where the relay is from the driver's packets
+        let mut buffer = [0u8; 64];
+        let relay_address = loop {
+            driver
+                .send_to(&[3, 0, 0, 0, 3], listen_address)
+                .await
+                .unwrap();
+
+            match tokio::time::timeout(Duration::from_millis(50), car.recv_from(&mut buffer)).await
+            {
+                Ok(Ok((_, address))) => break address,
+                _ => continue,
+            }
+        };
+
+        // A second of speeding up, then the car restarts its count from zero
+        // and brakes hard
+        let phases = [(1000..1050, 3.0), (0..50, -6.0)];
+        for (sequences, forward) in phases {
+            for sequence in sequences {
+                let mut motion =
+                    Motion::new(sequence, 2.0, [forward, 0.0, 9.81], 0.0, 0.0, [0.0, 0.0]);
+                car.send_to(&motion.generate_packet(), relay_address)
+                    .await
+                    .unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        }
+
+        // Everything the seat was sent
+        let mut cues: Vec<(u32, f32)> = Vec::new();
+        while let Ok(size) = seat.try_recv(&mut buffer) {
+            assert_eq!(size, CUE_PACKET_SIZE);
+            assert_eq!(buffer[0], cueing::CUE_FORMAT_VERSION);
+
+            let sequence = u32::from_be_bytes([buffer[1], buffer[2], buffer[3], buffer[4]]);
+            let surge = f32::from_be_bytes([buffer[5], buffer[6], buffer[7], buffer[8]]);
+            cues.push((sequence, surge));
+        }
+
+        assert!(cues.len() > 50);
+        assert!(cues.windows(2).all(|w| w[1].0 == w[0].0.wrapping_add(1)));
+
+        // Pushed forward while speeding up, and the braking after the restart
+        // got through and pulled the seat back
+        assert!(cues.iter().any(|(_, surge)| *surge > 0.01));
+        assert!(cues[cues.len() - 1].1 < 0.0);
+    }
+}
@@ -0,0 +1 @@
+ALTER TABLE cars ADD COLUMN calibration text;
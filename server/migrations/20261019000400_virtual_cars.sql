@@ -0,0 +1 @@
+ALTER TABLE cars ADD COLUMN is_virtual boolean NOT NULL DEFAULT false;
@@ -1,3 +1,4 @@
+use crate::lib::virtual_cars::VirtualCars;
 use crate::repo::database::postgres::PostgresDatabase;
 
 use lettre::SmtpTransport;
@@ -7,4 +8,6 @@ pub struct HttpState {
     pub jwt_secret: String,
     pub smtp_transport: SmtpTransport,
     pub from_address: String,
+    pub low_battery_percent: u8,
+    pub virtual_cars: VirtualCars,
 }
@@ -1,3 +1,5 @@
+use crate::repo::database::base::CarFull;
+
 use common_data::server::data::jwt_claims::AuthJwt;
 
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
@@ -80,3 +82,10 @@ pub fn validate_and_refresh(token: &str, secret: &String) -> Result<AuthState, (
         refresh_token: None,
     });
 }
+
+pub fn validate_car_key(api_key: &str, car: &CarFull) -> bool {
+    match bcrypt::verify(api_key, &car.secret) {
+        Ok(v) => v,
+        Err(_) => false,
+    }
+}
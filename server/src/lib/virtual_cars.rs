@@ -0,0 +1,242 @@
+use crate::repo::database::base::DataBase;
+use crate::repo::database::postgres::PostgresDatabase;
+
+use common_data::server::data::mission::Mission;
+
+use rc_car::agent::Agent;
+use rc_car::data::config::CarConfig;
+
+use tokio::net::UdpSocket;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration, Instant};
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+// Most ticks run at once when the loop falls behind
+const MAX_CATCH_UP: u32 = 5;
+
+#[derive(Debug)]
+pub enum VirtualCarError {
+    BindError,
+}
+
+struct VirtualCar {
+    address: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+// Cars simulated in the server. Each listens for its driver on its own UDP
+// port and writes telementry straight to the database, so to the user it looks
+// like any other car.
+pub struct VirtualCars {
+    pub database: PostgresDatabase,
+    // Every virtual car starts from this config
+    pub config: CarConfig,
+    // Address the control sockets bind to and are reported with
+    pub host: String,
+    runtime: Handle,
+    cars: Mutex<HashMap<String, VirtualCar>>,
+}
+
+impl VirtualCars {
+    pub fn new(database: PostgresDatabase, config: CarConfig, host: String) -> Self {
+        return VirtualCars {
+            database,
+            config,
+            host,
+            runtime: Handle::current(),
+            cars: Mutex::new(HashMap::new()),
+        };
+    }
+
+    // Starts simulating the car, or leaves it running if it already is.
+    // Returns the address drivers send control packets to.
+    pub fn start(&self, car_id: &str) -> Result<SocketAddr, VirtualCarError> {
+        let mut cars = match self.cars.lock() {
+            Ok(c) => c,
+            Err(p) => p.into_inner(),
+        };
+
+        if let Some(car) = cars.get(car_id) {
+            if !car.task.is_finished() {
+                return Ok(car.address);
+            }
+        }
+
+        let socket = match std::net::UdpSocket::bind((self.host.as_str(), 0)) {
+            Ok(s) => s,
+            Err(_) => return Err(VirtualCarError::BindError),
+        };
+
+        let address = match socket.local_addr() {
+            Ok(a) => a,
+            Err(_) => return Err(VirtualCarError::BindError),
+        };
+
+        if socket.set_nonblocking(true).is_err() {
+            return Err(VirtualCarError::BindError);
+        }
+
+        // Spawned on the main runtime, the HTTP workers each run their own
+        let task = self.runtime.spawn(run(
+            self.database.clone(),
+            car_id.to_string(),
+            self.config.clone(),
+            socket,
+        ));
+
+        cars.insert(car_id.to_string(), VirtualCar { address, task });
+
+        return Ok(address);
+    }
+
+    pub fn stop(&self, car_id: &str) {
+        let mut cars = match self.cars.lock() {
+            Ok(c) => c,
+            Err(p) => p.into_inner(),
+        };
+
+        if let Some(car) = cars.remove(car_id) {
+            car.task.abort();
+        }
+    }
+
+    pub fn control_address(&self, car_id: &str) -> Option<String> {
+        let cars = match self.cars.lock() {
+            Ok(c) => c,
+            Err(p) => p.into_inner(),
+        };
+
+        return cars.get(car_id).map(|c| c.address.to_string());
+    }
+}
+
+// Same loop as a real car running the sim, but reading and writing the
+// database instead of calling the server
+async fn run(
+    database: PostgresDatabase,
+    car_id: String,
+    config: CarConfig,
+    socket: std::net::UdpSocket,
+) {
+    let socket = match UdpSocket::from_std(socket) {
+        Ok(s) => s,
+        Err(_) => {
+            println!("Warning: Cannot start virtual car {}", car_id);
+            return;
+        }
+    };
+
+    let step = Duration::from_secs_f64(1.0 / f64::from(config.tick_rate.max(1)));
+    let mut tick = interval(step);
+    let mut report = interval(Duration::from_secs(config.report_interval.max(1)));
+
+    let mut agent = Agent::new(config);
+
+    match database.fetch_car(&car_id).await {
+        Ok(Some(car)) => {
+            if let Some(calibration) = car.calibration {
+                agent.calibration = calibration;
+            }
+        }
+        Ok(None) => return,
+        Err(_) => println!(
+            "Warning: Cannot fetch calibration for virtual car {}",
+            car_id
+        ),
+    };
+
+    if database
+        .put_car_capabilities(&car_id, &agent.capabilities())
+        .await
+        .is_err()
+    {
+        println!(
+            "Warning: Cannot save capabilities for virtual car {}",
+            car_id
+        );
+    }
+
+    let mut buffer = [0u8; 64];
+    let mut last_tick = Instant::now();
+    // Real time not yet simulated, stepped off in fixed ticks
+    let mut behind = Duration::ZERO;
+    // Where control packets last came from, pings go back there
+    let mut driver: Option<SocketAddr> = None;
+
+    // Missions are fetched off the control loop and handed back here
+    let (mission_tx, mut mission_rx) = mpsc::channel::<Mission>(1);
+    let mut started_mission: Option<String> = None;
+
+    loop {
+        tokio::select! {
+            received = socket.recv_from(&mut buffer) => {
+                let (size, address) = match received {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                };
+
+                driver = Some(address);
+                agent.handle_packet(&buffer[..size]);
+            }
+            Some(mission) = mission_rx.recv() => {
+                if started_mission.as_ref() == Some(&mission.uuid) {
+                    continue;
+                }
+
+                let uuid = mission.uuid.clone();
+
+                if agent.start_mission(mission) {
+                    started_mission = Some(uuid);
+                }
+            }
+            _ = tick.tick() => {
+                let now = Instant::now();
+                behind += now - last_tick;
+                last_tick = now;
+
+                // Catch up after a stall, but not so far the car jumps
+                behind = behind.min(step * MAX_CATCH_UP);
+
+                while behind >= step {
+                    agent.tick(step.as_secs_f32());
+                    behind -= step;
+                }
+
+                let packets = agent.take_packets();
+
+                if let Some(address) = driver {
+                    for packet in packets.iter() {
+                        let _ = socket.send_to(packet, address).await;
+                    }
+                }
+            }
+            _ = report.tick() => {
+                let database = database.clone();
+                let car_id = car_id.clone();
+                let telementry = agent.telementry();
+                let mission_tx = mission_tx.clone();
+
+                tokio::spawn(async move {
+                    if database.put_car_telementry(&car_id, &telementry).await.is_err() {
+                        println!("Warning: Cannot save telementry for virtual car {}", car_id);
+                    }
+
+                    match database.fetch_car(&car_id).await {
+                        Ok(Some(car)) => {
+                            if let Some(mission) = car.mission {
+                                let _ = mission_tx.send(mission).await;
+                            }
+                        }
+                        Ok(None) => (),
+                        Err(_) => println!("Warning: Cannot fetch mission for virtual car {}", car_id),
+                    };
+                });
+            }
+        }
+    }
+}
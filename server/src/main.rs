@@ -3,6 +3,7 @@ mod lib;
 mod repo;
 
 use dotenvy::dotenv;
+use lib::virtual_cars::VirtualCars;
 use repo::database::base::DataBase;
 use repo::database::postgres::PostgresDatabase;
 
@@ -11,6 +12,8 @@ use actix_web::HttpServer;
 
 use lettre::SmtpTransport;
 
+use rc_car::data::config::CarConfig;
+
 use std::env;
 
 #[tokio::main]
@@ -40,11 +43,46 @@ async fn main() {
         Err(_) => panic!("Cannot connect to SMTP relay"),
     };
 
+    let low_battery_percent = match env::var("LOW_BATTERY_PERCENT") {
+        Err(_) => 20,
+        Ok(v) => match v.parse::<u8>() {
+            Ok(p) if p <= 100 => p,
+            _ => panic!("LOW_BATTERY_PERCENT must be a number from 0 to 100"),
+        },
+    };
+
+    let virtual_car_host = match env::var("VIRTUAL_CAR_HOST") {
+        Err(_) => "127.0.0.1".to_string(),
+        Ok(v) => v,
+    };
+
+    // Car config every virtual car is simulated with
+    let virtual_car_config = match env::var("VIRTUAL_CAR_CONFIG") {
+        Err(_) => CarConfig::default(),
+        Ok(v) => CarConfig::load(&v).expect("Cannot load VIRTUAL_CAR_CONFIG"),
+    };
+
+    let virtual_cars = VirtualCars::new((*database).clone(), virtual_car_config, virtual_car_host);
+
+    // Virtual cars only exist while the server runs
+    let existing_virtual_cars = database
+        .fetch_virtual_cars()
+        .await
+        .expect("Cannot fetch virtual cars");
+
+    for car in existing_virtual_cars.iter() {
+        if virtual_cars.start(&car.uuid).is_err() {
+            println!("Warning: Cannot start virtual car {}", car.uuid);
+        }
+    }
+
     let http_state = crate::data::state::HttpState {
         database: *database,
         jwt_secret: jwt_secret,
         smtp_transport: smtp_transport.build(),
         from_address: from_address,
+        low_battery_percent: low_battery_percent,
+        virtual_cars: virtual_cars,
     };
 
     let web_data = actix_web::web::Data::new(http_state);
@@ -58,6 +96,14 @@ async fn main() {
             .service(crate::repo::http::user::cars::get)
             .service(crate::repo::http::user::cars::add)
             .service(crate::repo::http::user::cars::remove)
+            .service(crate::repo::http::user::mission::put)
+            .service(crate::repo::http::user::mission::remove)
+            .service(crate::repo::http::user::calibration::get)
+            .service(crate::repo::http::user::calibration::put)
+            .service(crate::repo::http::car::telementry::put)
+            .service(crate::repo::http::car::mission::get)
+            .service(crate::repo::http::car::calibration::get)
+            .service(crate::repo::http::car::capabilities::put)
     })
     .disable_signals()
     .bind(("127.0.0.1", 8080))
@@ -1,3 +1,7 @@
+use common_data::server::data::calibration::Calibration;
+use common_data::server::data::capabilities::Capabilities;
+use common_data::server::data::mission::Mission;
+use common_data::server::data::telementry::Telementry;
 use common_data::server::json::http::Car;
 
 use chrono::prelude::*;
@@ -19,9 +23,30 @@ pub trait DataBase {
     async fn delete_user_auth(&self, username: &String) -> Result<(), DatabaseError>;
     async fn fetch_cars_by_user(&self, username: &String) -> Result<Vec<Car>, DatabaseError>;
     async fn fetch_car(&self, car_id: &String) -> Result<Option<CarFull>, DatabaseError>;
+    async fn fetch_virtual_cars(&self) -> Result<Vec<CarFull>, DatabaseError>;
     async fn delete_car(&self, car_id: &String) -> Result<(), DatabaseError>;
     async fn put_car(&self, car: &CarFull) -> Result<(), DatabaseError>;
     async fn ping_car_state(&self, car_id: &String) -> Result<(), DatabaseError>;
+    async fn put_car_telementry(
+        &self,
+        car_id: &String,
+        telementry: &Telementry,
+    ) -> Result<(), DatabaseError>;
+    async fn put_car_mission(
+        &self,
+        car_id: &String,
+        mission: &Option<Mission>,
+    ) -> Result<(), DatabaseError>;
+    async fn put_car_calibration(
+        &self,
+        car_id: &String,
+        calibration: &Calibration,
+    ) -> Result<(), DatabaseError>;
+    async fn put_car_capabilities(
+        &self,
+        car_id: &String,
+        capabilities: &Capabilities,
+    ) -> Result<(), DatabaseError>;
 }
 
 #[derive(Debug, Clone)]
@@ -44,4 +69,9 @@ pub struct CarFull {
     pub username: String,
     pub last_updated: NaiveDateTime,
     pub last_ping: Option<NaiveDateTime>,
+    pub telementry: Option<Telementry>,
+    pub mission: Option<Mission>,
+    pub calibration: Option<Calibration>,
+    pub capabilities: Option<Capabilities>,
+    pub is_virtual: bool,
 }
@@ -1,5 +1,9 @@
 use crate::repo::database::base::{CarFull, DataBase, DatabaseError, User, UserAuth};
 
+use common_data::server::data::calibration::Calibration;
+use common_data::server::data::capabilities::Capabilities;
+use common_data::server::data::mission::Mission;
+use common_data::server::data::telementry::Telementry;
 use common_data::server::json::http::Car;
 
 use std::path::Path;
@@ -11,6 +15,9 @@ use sqlx::PgPool;
 use chrono::prelude::*;
 use chrono::TimeDelta;
 
+use serde::de::DeserializeOwned;
+use serde_json;
+
 #[derive(Clone)]
 pub struct PostgresDatabase {
     pool: Arc<PgPool>,
@@ -150,29 +157,41 @@ impl DataBase for PostgresDatabase {
         let offset = (Utc::now() - TimeDelta::try_minutes(2).unwrap()).naive_utc();
 
         for car in cars {
-            if car.last_ping.is_none() {
-                return_cars.push(Car {
-                    uuid: car.uuid,
-                    status: common_data::server::json::http::CarState::Offline,
-                    name: car.name,
-                })
-            } else {
-                let compare_time = car.last_ping.clone().unwrap();
-
-                if compare_time >= offset {
-                    return_cars.push(Car {
-                        uuid: car.uuid,
-                        status: common_data::server::json::http::CarState::Online,
-                        name: car.name,
-                    })
-                } else {
-                    return_cars.push(Car {
-                        uuid: car.uuid,
-                        status: common_data::server::json::http::CarState::Offline,
-                        name: car.name,
-                    })
-                }
-            }
+            let telementry: Option<Telementry> = parse_json(&car.telementry);
+
+            let online = match car.last_ping {
+                Some(p) => p >= offset,
+                None => false,
+            };
+
+            let status = match online {
+                true => common_data::server::json::http::CarState::Online,
+                false => common_data::server::json::http::CarState::Offline,
+            };
+
+            // A link throttle only applies while the car is connected
+            let (battery_charge, throttle_cap, throttle_cap_reason) = match telementry {
+                Some(t) if online => (
+                    Some(t.battery_charge),
+                    t.throttle_cap,
+                    t.throttle_cap_reason,
+                ),
+                Some(t) => (Some(t.battery_charge), None, None),
+                None => (None, None, None),
+            };
+
+            return_cars.push(Car {
+                uuid: car.uuid,
+                status,
+                name: car.name,
+                battery_charge,
+                low_battery: false,
+                capabilities: parse_json(&car.capabilities),
+                throttle_cap,
+                throttle_cap_reason,
+                is_virtual: car.is_virtual,
+                control_address: None,
+            })
         }
 
         Ok(return_cars)
@@ -196,10 +215,46 @@ impl DataBase for PostgresDatabase {
                 username: c.username,
                 last_updated: c.last_updated,
                 last_ping: c.last_ping,
+                telementry: parse_json(&c.telementry),
+                mission: parse_json(&c.mission),
+                calibration: parse_json(&c.calibration),
+                capabilities: parse_json(&c.capabilities),
+                is_virtual: c.is_virtual,
             })),
         }
     }
 
+    async fn fetch_virtual_cars(&self) -> Result<Vec<CarFull>, DatabaseError> {
+        let cars = sqlx::query!("SELECT * from cars where is_virtual = true")
+            .fetch_all(&*self.pool)
+            .await;
+
+        let cars = match cars {
+            Ok(c) => c,
+            Err(_) => return Err(DatabaseError::ServerError),
+        };
+
+        let mut return_cars: Vec<CarFull> = Vec::new();
+
+        for c in cars {
+            return_cars.push(CarFull {
+                uuid: c.uuid,
+                name: c.name,
+                secret: c.secret,
+                username: c.username,
+                last_updated: c.last_updated,
+                last_ping: c.last_ping,
+                telementry: parse_json(&c.telementry),
+                mission: parse_json(&c.mission),
+                calibration: parse_json(&c.calibration),
+                capabilities: parse_json(&c.capabilities),
+                is_virtual: c.is_virtual,
+            });
+        }
+
+        Ok(return_cars)
+    }
+
     async fn put_car(&self, car: &CarFull) -> Result<(), DatabaseError> {
         let car_opt = self.fetch_car(&car.uuid).await;
 
@@ -224,11 +279,12 @@ impl DataBase for PostgresDatabase {
             }
         } else {
             let query_status = sqlx::query!(
-                "INSERT INTO cars (uuid, secret, name, username) VALUES($1, $2, $3, $4)",
+                "INSERT INTO cars (uuid, secret, name, username, is_virtual) VALUES($1, $2, $3, $4, $5)",
                 car.uuid,
                 car.secret,
                 car.name,
-                car.username
+                car.username,
+                car.is_virtual
             )
             .execute(&*self.pool)
             .await;
@@ -298,4 +354,113 @@ impl DataBase for PostgresDatabase {
             Err(_) => Err(DatabaseError::ServerError),
         }
     }
+
+    async fn put_car_telementry(
+        &self,
+        car_id: &String,
+        telementry: &Telementry,
+    ) -> Result<(), DatabaseError> {
+        let telementry_string = match serde_json::to_string(telementry) {
+            Ok(s) => s,
+            Err(_) => return Err(DatabaseError::ServerError),
+        };
+
+        let query = sqlx::query!(
+            "UPDATE cars SET telementry = $2, last_ping = (NOW() at time zone 'utc') WHERE uuid = $1",
+            car_id,
+            telementry_string
+        )
+        .execute(&*self.pool)
+        .await;
+
+        match query {
+            Ok(_) => Ok(()),
+            Err(_) => Err(DatabaseError::QueryError),
+        }
+    }
+
+    async fn put_car_mission(
+        &self,
+        car_id: &String,
+        mission: &Option<Mission>,
+    ) -> Result<(), DatabaseError> {
+        let mission_string = match mission {
+            None => None,
+            Some(m) => match serde_json::to_string(m) {
+                Ok(s) => Some(s),
+                Err(_) => return Err(DatabaseError::ServerError),
+            },
+        };
+
+        let query = sqlx::query!(
+            "UPDATE cars SET mission = $2 WHERE uuid = $1",
+            car_id,
+            mission_string
+        )
+        .execute(&*self.pool)
+        .await;
+
+        match query {
+            Ok(_) => Ok(()),
+            Err(_) => Err(DatabaseError::QueryError),
+        }
+    }
+
+    async fn put_car_calibration(
+        &self,
+        car_id: &String,
+        calibration: &Calibration,
+    ) -> Result<(), DatabaseError> {
+        let calibration_string = match serde_json::to_string(calibration) {
+            Ok(s) => s,
+            Err(_) => return Err(DatabaseError::ServerError),
+        };
+
+        let query = sqlx::query!(
+            "UPDATE cars SET calibration = $2 WHERE uuid = $1",
+            car_id,
+            calibration_string
+        )
+        .execute(&*self.pool)
+        .await;
+
+        match query {
+            Ok(_) => Ok(()),
+            Err(_) => Err(DatabaseError::QueryError),
+        }
+    }
+
+    async fn put_car_capabilities(
+        &self,
+        car_id: &String,
+        capabilities: &Capabilities,
+    ) -> Result<(), DatabaseError> {
+        let capabilities_string = match serde_json::to_string(capabilities) {
+            Ok(s) => s,
+            Err(_) => return Err(DatabaseError::ServerError),
+        };
+
+        let query = sqlx::query!(
+            "UPDATE cars SET capabilities = $2 WHERE uuid = $1",
+            car_id,
+            capabilities_string
+        )
+        .execute(&*self.pool)
+        .await;
+
+        match query {
+            Ok(_) => Ok(()),
+            Err(_) => Err(DatabaseError::QueryError),
+        }
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(column: &Option<String>) -> Option<T> {
+    match column {
+        None => None,
+        Some(t) => match serde_json::from_str(t) {
+            Ok(o) => Some(o),
+            Err(_) => None,
+        },
+    }
 }
@@ -0,0 +1,45 @@
+use crate::data::state::HttpState;
+use crate::lib::auth;
+use crate::repo::database::base::DataBase;
+
+use actix_web::get;
+use actix_web::web::Data;
+use actix_web::web::Path;
+use actix_web::HttpRequest;
+use actix_web::HttpResponse;
+use actix_web::Responder;
+
+use serde_json;
+
+#[get("/car/{car_id}/calibration")]
+async fn get(state: Data<HttpState>, req: HttpRequest, path: Path<(String,)>) -> impl Responder {
+    let car_uuid = path.into_inner().0;
+
+    let api_key = match req.headers().get("Authorization") {
+        None => return HttpResponse::Unauthorized().body("No authorization token"),
+        Some(ah) => match ah.to_str() {
+            Err(_) => return HttpResponse::BadRequest().body("Bad authorization token"),
+            Ok(k) => k.to_string(),
+        },
+    };
+
+    let car = state.database.fetch_car(&car_uuid).await;
+
+    let car = match car {
+        Ok(co) => match co {
+            Some(c) => c,
+            None => return HttpResponse::NotFound().body("Car does not exist"),
+        },
+        Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
+    };
+
+    if !auth::validate_car_key(&api_key, &car) {
+        return HttpResponse::Unauthorized().body("Not Authorized");
+    }
+
+    // Cars that were never calibrated get the neutral profile
+    match serde_json::to_string(&car.calibration.unwrap_or_default()) {
+        Ok(s) => HttpResponse::Ok().body(s),
+        Err(_) => HttpResponse::ServiceUnavailable().body("Server Error"),
+    }
+}
@@ -0,0 +1,59 @@
+use crate::data::state::HttpState;
+use crate::lib::auth;
+use crate::repo::database::base::DataBase;
+
+use common_data::server::data::capabilities::Capabilities;
+
+use actix_web::put;
+use actix_web::web::Data;
+use actix_web::web::Json;
+use actix_web::web::Path;
+use actix_web::HttpRequest;
+use actix_web::HttpResponse;
+use actix_web::Responder;
+
+#[put("/car/{car_id}/capabilities")]
+async fn put(
+    state: Data<HttpState>,
+    req: HttpRequest,
+    path: Path<(String,)>,
+    data: Json<Capabilities>,
+) -> impl Responder {
+    let car_uuid = path.into_inner().0;
+
+    let api_key = match req.headers().get("Authorization") {
+        None => return HttpResponse::Unauthorized().body("No authorization token"),
+        Some(ah) => match ah.to_str() {
+            Err(_) => return HttpResponse::BadRequest().body("Bad authorization token"),
+            Ok(k) => k.to_string(),
+        },
+    };
+
+    let car = state.database.fetch_car(&car_uuid).await;
+
+    let car = match car {
+        Ok(co) => match co {
+            Some(c) => c,
+            None => return HttpResponse::NotFound().body("Car does not exist"),
+        },
+        Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
+    };
+
+    if !auth::validate_car_key(&api_key, &car) {
+        return HttpResponse::Unauthorized().body("Not Authorized");
+    }
+
+    if data.commands.is_empty() || data.axes.is_empty() || data.max_speed < 0.0 {
+        return HttpResponse::BadRequest().body("Capabilities need commands, axes and a speed");
+    }
+
+    let capabilities_query = state
+        .database
+        .put_car_capabilities(&car_uuid, &data.into_inner())
+        .await;
+
+    match capabilities_query {
+        Ok(_) => HttpResponse::Ok().finish(),
+        Err(_) => HttpResponse::ServiceUnavailable().body("Server Error"),
+    }
+}
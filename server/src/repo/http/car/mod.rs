@@ -0,0 +1,4 @@
+pub mod calibration;
+pub mod capabilities;
+pub mod mission;
+pub mod telementry;
@@ -0,0 +1,122 @@
+use crate::data::state::HttpState;
+use crate::lib::auth;
+use crate::repo::database::base::DataBase;
+
+use common_data::server::data::calibration::Calibration;
+
+use actix_web::get;
+use actix_web::put;
+use actix_web::web::Data;
+use actix_web::web::Json;
+use actix_web::web::Path;
+use actix_web::HttpRequest;
+use actix_web::HttpResponse;
+use actix_web::Responder;
+
+use serde_json;
+
+#[get("/user/cars/{car_id}/calibration")]
+async fn get(state: Data<HttpState>, req: HttpRequest, path: Path<(String,)>) -> impl Responder {
+    let auth_token = req.headers().get("Authorization");
+
+    let auth_state = match auth_token {
+        None => return HttpResponse::Unauthorized().body("No authorization token"),
+        Some(ah) => match ah.to_str() {
+            Err(_) => return HttpResponse::BadRequest().body("Bad authorization token"),
+            Ok(ast) => match auth::validate_and_refresh(&ast, &state.jwt_secret) {
+                Err(_) => return HttpResponse::BadRequest().body("Bad authorization token"),
+                Ok(a) => a,
+            },
+        },
+    };
+
+    let car_uuid = path.into_inner().0;
+
+    let car = state.database.fetch_car(&car_uuid).await;
+
+    let car = match car {
+        Ok(co) => match co {
+            Some(c) => c,
+            None => return HttpResponse::NotFound().body("Car does not exist"),
+        },
+        Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
+    };
+
+    if car.username != auth_state.claims.email {
+        return HttpResponse::Unauthorized().body("Not Authorized");
+    }
+
+    let calibration = car.calibration.unwrap_or_default();
+
+    let return_string = match serde_json::to_string(&calibration) {
+        Ok(s) => s,
+        Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
+    };
+
+    match auth_state.refresh_token {
+        None => HttpResponse::Ok().body(return_string),
+        Some(t) => HttpResponse::Ok()
+            .insert_header(("Authorization", t))
+            .body(return_string),
+    }
+}
+
+#[put("/user/cars/{car_id}/calibration")]
+async fn put(
+    state: Data<HttpState>,
+    req: HttpRequest,
+    path: Path<(String,)>,
+    data: Json<Calibration>,
+) -> impl Responder {
+    let auth_token = req.headers().get("Authorization");
+
+    let auth_state = match auth_token {
+        None => return HttpResponse::Unauthorized().body("No authorization token"),
+        Some(ah) => match ah.to_str() {
+            Err(_) => return HttpResponse::BadRequest().body("Bad authorization token"),
+            Ok(ast) => match auth::validate_and_refresh(&ast, &state.jwt_secret) {
+                Err(_) => return HttpResponse::BadRequest().body("Bad authorization token"),
+                Ok(a) => a,
+            },
+        },
+    };
+
+    let car_uuid = path.into_inner().0;
+
+    let car = state.database.fetch_car(&car_uuid).await;
+
+    let car = match car {
+        Ok(co) => match co {
+            Some(c) => c,
+            None => return HttpResponse::NotFound().body("Car does not exist"),
+        },
+        Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
+    };
+
+    if car.username != auth_state.claims.email {
+        return HttpResponse::Unauthorized().body("Not Authorized");
+    }
+
+    if !data.is_valid() {
+        return HttpResponse::BadRequest()
+            .body("Trim must be -100 to 100 and limits and endpoints 0 to 100");
+    }
+
+    let calibration_query = state.database.put_car_calibration(&car_uuid, &data).await;
+
+    if calibration_query.is_err() {
+        return HttpResponse::ServiceUnavailable().body("Server Error");
+    }
+
+    let return_string = match serde_json::to_string(&data.into_inner()) {
+        Ok(s) => s,
+        Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
+    };
+
+    match auth_state.refresh_token {
+        None => HttpResponse::Ok().body(return_string),
+        Some(t) => HttpResponse::Ok()
+            .insert_header(("Authorization", t))
+            .body(return_string),
+    }
+}
@@ -1,5 +1,6 @@
 use crate::data::state::HttpState;
 use crate::lib::auth;
+use crate::lib::virtual_cars::VirtualCars;
 use crate::repo::database::base::{CarFull, DataBase};
 
 use common_data::server::json::http::{Car, CarState, CreateCar, CreateCarReturn, GetCars};
@@ -24,6 +25,23 @@ use rand::{distributions::Alphanumeric, Rng};
 
 use chrono::prelude::*;
 
+fn flag_low_battery(cars: &mut Vec<Car>, low_battery_percent: u8) {
+    for car in cars.iter_mut() {
+        car.low_battery = match car.battery_charge {
+            Some(b) => b <= low_battery_percent,
+            None => false,
+        };
+    }
+}
+
+fn fill_control_addresses(cars: &mut [Car], virtual_cars: &VirtualCars) {
+    for car in cars.iter_mut() {
+        if car.is_virtual {
+            car.control_address = virtual_cars.control_address(&car.uuid);
+        }
+    }
+}
+
 #[get("/user/cars")]
 async fn get(state: Data<HttpState>, req: HttpRequest) -> impl Responder {
     let auth_token = req.headers().get("Authorization");
@@ -44,11 +62,14 @@ async fn get(state: Data<HttpState>, req: HttpRequest) -> impl Responder {
         .fetch_cars_by_user(&auth_state.claims.email)
         .await;
 
-    let cars = match cars {
+    let mut cars = match cars {
         Ok(c) => c,
         Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
     };
 
+    flag_low_battery(&mut cars, state.low_battery_percent);
+    fill_control_addresses(&mut cars, &state.virtual_cars);
+
     let return_struct = GetCars { cars: cars };
 
     let return_string = match serde_json::to_string(&return_struct) {
@@ -140,6 +161,11 @@ async fn add(state: Data<HttpState>, req: HttpRequest, data: Json<CreateCar>) ->
             username: auth_state.claims.email.clone(),
             last_ping: None,
             last_updated: Utc::now().naive_utc(),
+            telementry: None,
+            mission: None,
+            calibration: None,
+            capabilities: None,
+            is_virtual: data.is_virtual,
         })
         .await;
 
@@ -147,6 +173,11 @@ async fn add(state: Data<HttpState>, req: HttpRequest, data: Json<CreateCar>) ->
         return HttpResponse::ServiceUnavailable().body("Server Error");
     }
 
+    if data.is_virtual && state.virtual_cars.start(&car_uuid).is_err() {
+        let _ = state.database.delete_car(&car_uuid).await;
+        return HttpResponse::ServiceUnavailable().body("Cannot start virtual car");
+    }
+
     let return_car = CreateCarReturn {
         name: data.name.clone(),
         uuid: car_uuid.clone(),
@@ -196,15 +227,22 @@ async fn remove(state: Data<HttpState>, req: HttpRequest, path: Path<(String,)>)
         return HttpResponse::ServiceUnavailable().body("Server Error");
     }
 
+    if car.is_virtual {
+        state.virtual_cars.stop(&car_uuid);
+    }
+
     let cars = state
         .database
         .fetch_cars_by_user(&auth_state.claims.email)
         .await;
-    let cars = match cars {
+    let mut cars = match cars {
         Ok(c) => c,
         Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
     };
 
+    flag_low_battery(&mut cars, state.low_battery_percent);
+    fill_control_addresses(&mut cars, &state.virtual_cars);
+
     let return_struct = GetCars { cars: cars };
 
     let return_string = match serde_json::to_string(&return_struct) {
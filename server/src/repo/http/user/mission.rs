@@ -0,0 +1,149 @@
+use crate::data::state::HttpState;
+use crate::lib::auth;
+use crate::repo::database::base::DataBase;
+
+use common_data::server::data::mission::Mission;
+use common_data::server::json::http::CreateMission;
+
+use actix_web::delete;
+use actix_web::put;
+use actix_web::web::Data;
+use actix_web::web::Json;
+use actix_web::web::Path;
+use actix_web::HttpRequest;
+use actix_web::HttpResponse;
+use actix_web::Responder;
+
+use serde_json;
+
+use uuid::Uuid;
+
+#[put("/user/cars/{car_id}/mission")]
+async fn put(
+    state: Data<HttpState>,
+    req: HttpRequest,
+    path: Path<(String,)>,
+    data: Json<CreateMission>,
+) -> impl Responder {
+    let auth_token = req.headers().get("Authorization");
+
+    let auth_state = match auth_token {
+        None => return HttpResponse::Unauthorized().body("No authorization token"),
+        Some(ah) => match ah.to_str() {
+            Err(_) => return HttpResponse::BadRequest().body("Bad authorization token"),
+            Ok(ast) => match auth::validate_and_refresh(&ast, &state.jwt_secret) {
+                Err(_) => return HttpResponse::BadRequest().body("Bad authorization token"),
+                Ok(a) => a,
+            },
+        },
+    };
+
+    let car_uuid = path.into_inner().0;
+
+    let car = state.database.fetch_car(&car_uuid).await;
+
+    let car = match car {
+        Ok(co) => match co {
+            Some(c) => c,
+            None => return HttpResponse::NotFound().body("Car does not exist"),
+        },
+        Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
+    };
+
+    if car.username != auth_state.claims.email {
+        return HttpResponse::Unauthorized().body("Not Authorized");
+    }
+
+    if data.waypoints.is_empty() || data.waypoints.len() > 500 {
+        return HttpResponse::BadRequest().body("A mission needs between 1 and 500 waypoints");
+    }
+
+    if data.laps < 1 || data.laps > 100 {
+        return HttpResponse::BadRequest().body("A mission needs between 1 and 100 laps");
+    }
+
+    for waypoint in data.waypoints.iter() {
+        if waypoint.latitude.abs() > 90.0 || waypoint.longitude.abs() > 180.0 {
+            return HttpResponse::BadRequest().body("Waypoint is not a valid position");
+        }
+
+        match waypoint.speed {
+            Some(s) if s <= 0.0 => {
+                return HttpResponse::BadRequest().body("Waypoint speed must be above 0")
+            }
+            _ => (),
+        };
+    }
+
+    let mission = Mission {
+        uuid: Uuid::new_v4().to_string(),
+        waypoints: data.waypoints.clone(),
+        laps: data.laps,
+    };
+
+    let mission_query = state
+        .database
+        .put_car_mission(&car_uuid, &Some(mission.clone()))
+        .await;
+
+    if mission_query.is_err() {
+        return HttpResponse::ServiceUnavailable().body("Server Error");
+    }
+
+    let return_string = match serde_json::to_string(&mission) {
+        Ok(s) => s,
+        Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
+    };
+
+    match auth_state.refresh_token {
+        None => HttpResponse::Ok().body(return_string),
+        Some(t) => HttpResponse::Ok()
+            .insert_header(("Authorization", t))
+            .body(return_string),
+    }
+}
+
+#[delete("/user/cars/{car_id}/mission")]
+async fn remove(state: Data<HttpState>, req: HttpRequest, path: Path<(String,)>) -> impl Responder {
+    let auth_token = req.headers().get("Authorization");
+
+    let auth_state = match auth_token {
+        None => return HttpResponse::Unauthorized().body("No authorization token"),
+        Some(ah) => match ah.to_str() {
+            Err(_) => return HttpResponse::BadRequest().body("Bad authorization token"),
+            Ok(ast) => match auth::validate_and_refresh(&ast, &state.jwt_secret) {
+                Err(_) => return HttpResponse::BadRequest().body("Bad authorization token"),
+                Ok(a) => a,
+            },
+        },
+    };
+
+    let car_uuid = path.into_inner().0;
+
+    let car = state.database.fetch_car(&car_uuid).await;
+
+    let car = match car {
+        Ok(co) => match co {
+            Some(c) => c,
+            None => return HttpResponse::NotFound().body("Car does not exist"),
+        },
+        Err(_) => return HttpResponse::ServiceUnavailable().body("Server Error"),
+    };
+
+    if car.username != auth_state.claims.email {
+        return HttpResponse::Unauthorized().body("Not Authorized");
+    }
+
+    let mission_query = state.database.put_car_mission(&car_uuid, &None).await;
+
+    if mission_query.is_err() {
+        return HttpResponse::ServiceUnavailable().body("Server Error");
+    }
+
+    match auth_state.refresh_token {
+        None => HttpResponse::Ok().finish(),
+        Some(t) => HttpResponse::Ok()
+            .insert_header(("Authorization", t))
+            .finish(),
+    }
+}
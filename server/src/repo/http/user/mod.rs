@@ -1 +1,3 @@
+pub mod calibration;
 pub mod cars;
+pub mod mission;